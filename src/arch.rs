@@ -0,0 +1,309 @@
+//! Thin wrappers around x86_64 instructions the rest of the kernel needs
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    arch::{
+        asm,
+        x86_64::__cpuid,
+    },
+    fmt,
+    mem::offset_of,
+};
+
+pub mod entry;
+mod features;
+mod sse;
+
+#[allow(unused_imports)]
+pub use features::{
+    Feature,
+    has_feature,
+    verify_requirements,
+};
+pub use sse::enable_sse;
+
+/// CPUID leaf 0 returns the vendor string split across EBX, EDX, ECX
+/// (in that order), e.g. "GenuineIntel" or "AuthenticAMD".
+pub fn cpu_vendor() -> [u8; 12] {
+    let leaf = __cpuid(0);
+    let mut vendor = [0; 12];
+    vendor[0..4].copy_from_slice(&leaf.ebx.to_le_bytes());
+    vendor[4..8].copy_from_slice(&leaf.edx.to_le_bytes());
+    vendor[8..12].copy_from_slice(&leaf.ecx.to_le_bytes());
+    vendor
+}
+
+/// Stop this core for good. Interrupts go off first so nothing can wake
+/// it from hlt, and the loop covers NMIs, which still can.
+pub fn halt() -> ! {
+    crate::interrupts::disable();
+    loop {
+        unsafe { asm!("hlt", options(nomem, nostack)) };
+    }
+}
+
+/// A snapshot of the general purpose and control registers.
+///
+/// The asm in capture addresses the fields through offset_of!, so the
+/// field order here only affects how the dump is printed.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RegisterDump {
+    pub rax: u64,
+    pub rbx: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub rbp: u64,
+    pub rsp: u64,
+    pub r8: u64,
+    pub r9: u64,
+    pub r10: u64,
+    pub r11: u64,
+    pub r12: u64,
+    pub r13: u64,
+    pub r14: u64,
+    pub r15: u64,
+    pub rip: u64,
+    pub rflags: u64,
+    pub cr0: u64,
+    pub cr2: u64,
+    pub cr3: u64,
+    pub cr4: u64,
+}
+
+impl RegisterDump {
+    /// Always inlined so the snapshot reflects the caller's registers
+    /// rather than a fresh stack frame. All sixteen GPRs are stored before
+    /// the scratch register is touched. The one register the compiler picks
+    /// to hold the snapshot's address necessarily reports that address.
+    #[inline(always)]
+    pub fn capture() -> Self {
+        let mut dump = Self::default();
+        unsafe {
+            asm!(
+                "mov [{dump} + {rax}], rax",
+                "mov [{dump} + {rbx}], rbx",
+                "mov [{dump} + {rcx}], rcx",
+                "mov [{dump} + {rdx}], rdx",
+                "mov [{dump} + {rsi}], rsi",
+                "mov [{dump} + {rdi}], rdi",
+                "mov [{dump} + {rbp}], rbp",
+                "mov [{dump} + {rsp}], rsp",
+                "mov [{dump} + {r8}], r8",
+                "mov [{dump} + {r9}], r9",
+                "mov [{dump} + {r10}], r10",
+                "mov [{dump} + {r11}], r11",
+                "mov [{dump} + {r12}], r12",
+                "mov [{dump} + {r13}], r13",
+                "mov [{dump} + {r14}], r14",
+                "mov [{dump} + {r15}], r15",
+                // The label marks the instruction we report as RIP.
+                "2:",
+                "lea {scratch}, [rip + 2b]",
+                "mov [{dump} + {rip}], {scratch}",
+                "pushfq",
+                "pop {scratch}",
+                "mov [{dump} + {rflags}], {scratch}",
+                "mov {scratch}, cr0",
+                "mov [{dump} + {cr0}], {scratch}",
+                "mov {scratch}, cr2",
+                "mov [{dump} + {cr2}], {scratch}",
+                "mov {scratch}, cr3",
+                "mov [{dump} + {cr3}], {scratch}",
+                "mov {scratch}, cr4",
+                "mov [{dump} + {cr4}], {scratch}",
+                dump = in(reg) &mut dump,
+                scratch = out(reg) _,
+                rax = const offset_of!(RegisterDump, rax),
+                rbx = const offset_of!(RegisterDump, rbx),
+                rcx = const offset_of!(RegisterDump, rcx),
+                rdx = const offset_of!(RegisterDump, rdx),
+                rsi = const offset_of!(RegisterDump, rsi),
+                rdi = const offset_of!(RegisterDump, rdi),
+                rbp = const offset_of!(RegisterDump, rbp),
+                rsp = const offset_of!(RegisterDump, rsp),
+                r8 = const offset_of!(RegisterDump, r8),
+                r9 = const offset_of!(RegisterDump, r9),
+                r10 = const offset_of!(RegisterDump, r10),
+                r11 = const offset_of!(RegisterDump, r11),
+                r12 = const offset_of!(RegisterDump, r12),
+                r13 = const offset_of!(RegisterDump, r13),
+                r14 = const offset_of!(RegisterDump, r14),
+                r15 = const offset_of!(RegisterDump, r15),
+                rip = const offset_of!(RegisterDump, rip),
+                rflags = const offset_of!(RegisterDump, rflags),
+                cr0 = const offset_of!(RegisterDump, cr0),
+                cr2 = const offset_of!(RegisterDump, cr2),
+                cr3 = const offset_of!(RegisterDump, cr3),
+                cr4 = const offset_of!(RegisterDump, cr4),
+            );
+        }
+        dump
+    }
+}
+
+impl fmt::Display for RegisterDump {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let registers = [
+            ("RAX", self.rax), ("RBX", self.rbx), ("RCX", self.rcx),
+            ("RDX", self.rdx), ("RSI", self.rsi), ("RDI", self.rdi),
+            ("RBP", self.rbp), ("RSP", self.rsp), ("R8", self.r8),
+            ("R9", self.r9), ("R10", self.r10), ("R11", self.r11),
+            ("R12", self.r12), ("R13", self.r13), ("R14", self.r14),
+            ("R15", self.r15), ("RIP", self.rip), ("RFLAGS", self.rflags),
+            ("CR0", self.cr0), ("CR2", self.cr2), ("CR3", self.cr3),
+            ("CR4", self.cr4),
+        ];
+
+        write_register_grid(f, &registers)
+    }
+}
+
+/// Prints three registers per line so the grid fits in 80 columns.
+fn write_register_grid(f: &mut fmt::Formatter, registers: &[(&str, u64)]) -> fmt::Result {
+    for row in registers.chunks(3) {
+        for (name, value) in row {
+            write!(f, "{:>6}={:016x}  ", name, value)?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+/// Snapshot the registers first, then print them, so the printing code
+/// can't disturb the values being reported. Safe to call from a handler.
+#[allow(dead_code)]
+#[inline(always)]
+pub fn dump_registers() {
+    let dump = RegisterDump::capture();
+    crate::print!("{}", dump);
+}
+
+/// The registers an interrupt entry stub saves, in stack order from the
+/// final stack pointer upwards. The stub pushes RAX first and R15 last,
+/// on top of the frame the CPU pushed, so the struct starts at R15:
+/// ==  Offset  ==|== Registers ==
+///    0x00-0x38  |   R15, R14, R13, R12, R11, R10, R9, R8
+///    0x40-0x70  |   RBP, RDI, RSI, RDX, RCX, RBX, RAX
+///    0x78-0x98  |   RIP, CS, RFLAGS, RSP, SS (pushed by the CPU)
+///
+/// An error code, for the vectors that push one, must be popped before
+/// the stub starts pushing. The assembly and this layout have to agree
+/// byte for byte, which the assertions below pin down.
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+pub struct Registers {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rbp: u64,
+    pub rdi: u64,
+    pub rsi: u64,
+    pub rdx: u64,
+    pub rcx: u64,
+    pub rbx: u64,
+    pub rax: u64,
+    pub rip: u64,
+    pub cs: u64,
+    pub rflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+}
+
+const _: () = {
+    assert!(size_of::<Registers>() == 20 * 8);
+    assert!(offset_of!(Registers, r15) == 0x00);
+    assert!(offset_of!(Registers, r8) == 0x38);
+    assert!(offset_of!(Registers, rbp) == 0x40);
+    assert!(offset_of!(Registers, rax) == 0x70);
+    assert!(offset_of!(Registers, rip) == 0x78);
+    assert!(offset_of!(Registers, cs) == 0x80);
+    assert!(offset_of!(Registers, rflags) == 0x88);
+    assert!(offset_of!(Registers, rsp) == 0x90);
+    assert!(offset_of!(Registers, ss) == 0x98);
+};
+
+/// Same grid as RegisterDump, in the familiar RAX-first order rather
+/// than stack order.
+impl fmt::Debug for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let registers = [
+            ("RAX", self.rax), ("RBX", self.rbx), ("RCX", self.rcx),
+            ("RDX", self.rdx), ("RSI", self.rsi), ("RDI", self.rdi),
+            ("RBP", self.rbp), ("RSP", self.rsp), ("R8", self.r8),
+            ("R9", self.r9), ("R10", self.r10), ("R11", self.r11),
+            ("R12", self.r12), ("R13", self.r13), ("R14", self.r14),
+            ("R15", self.r15), ("RIP", self.rip), ("RFLAGS", self.rflags),
+            ("CS", self.cs), ("SS", self.ss),
+        ];
+
+        write_register_grid(f, &registers)
+    }
+}
+
+/// Read the time stamp counter. The lfence keeps rdtsc from running
+/// ahead of the instructions before it, so back-to-back reads bracket
+/// exactly the code between them.
+#[allow(dead_code)]
+pub fn read_tsc() -> u64 {
+    let low: u32;
+    let high: u32;
+    unsafe { asm!("lfence", "rdtsc", out("eax") low, out("edx") high, options(nomem, nostack, preserves_flags)) };
+    (high as u64) << 32 | low as u64
+}
+
+/// Safety: model specific registers control CPU features, so the MSR has
+/// to exist and writing the wrong value can change how the whole kernel
+/// executes.
+pub(crate) unsafe fn read_msr(msr: u32) -> u64 {
+    let low: u32;
+    let high: u32;
+    unsafe {
+        asm!(
+            "rdmsr",
+            in("ecx") msr,
+            out("eax") low,
+            out("edx") high,
+            options(nomem, nostack, preserves_flags),
+        )
+    };
+    (high as u64) << 32 | low as u64
+}
+
+/// Safety: see read_msr.
+pub(crate) unsafe fn write_msr(msr: u32, value: u64) {
+    unsafe {
+        asm!(
+            "wrmsr",
+            in("ecx") msr,
+            in("eax") value as u32,
+            in("edx") (value >> 32) as u32,
+            options(nostack, preserves_flags),
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::paging::{
+        self,
+        ADDRESS_MASK,
+    };
+
+    #[test_case]
+    fn register_dump_sees_the_active_cr3() {
+        let dump = RegisterDump::capture();
+        assert_eq!(dump.cr3 & ADDRESS_MASK, paging::active_level_4_table());
+    }
+}
@@ -0,0 +1,180 @@
+//! Adapts whichever boot protocol is active into a common kernel_main call
+//!
+//! kernel_entry! defines _start for the kernel binary, the test kernel,
+//! and each integration test under tests/, so they all boot the same way.
+//!
+//! The protocol is picked with exactly one cargo feature:
+//! - bios: a bare _start that gets no information from the bootloader
+//! - bootloader: bootloader 0.9's entry point, which hands us a BootInfo
+//! - uefi: not supported until we move to a bootloader that can do UEFI
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+#[cfg(any(
+    all(feature = "bios", feature = "bootloader"),
+    not(any(feature = "bios", feature = "bootloader", feature = "uefi")),
+))]
+compile_error!("enable exactly one boot protocol feature: bios, bootloader, or uefi");
+
+#[cfg(feature = "uefi")]
+compile_error!("the uefi boot protocol needs a newer bootloader than 0.9");
+
+/// bootloader 0.9 never reports more regions than this.
+pub const MAX_MEMORY_REGIONS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryKind {
+    /// Free for the kernel to use.
+    Usable,
+    /// Firmware, the kernel image, bootloader data, or anything else that
+    /// must be left alone.
+    Reserved,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryRegion {
+    pub start: u64,
+    pub end: u64,
+    pub kind: MemoryKind,
+}
+
+/// A fixed-capacity copy of the physical memory map, so it can be built
+/// before there is any heap to put it in.
+pub struct MemoryMap {
+    regions: [MemoryRegion; MAX_MEMORY_REGIONS],
+    len: usize,
+}
+
+impl MemoryMap {
+    const fn empty() -> Self {
+        Self {
+            regions: [MemoryRegion { start: 0, end: 0, kind: MemoryKind::Reserved }; MAX_MEMORY_REGIONS],
+            len: 0,
+        }
+    }
+
+    #[cfg(feature = "bootloader")]
+    fn push(&mut self, region: MemoryRegion) {
+        if self.len < MAX_MEMORY_REGIONS {
+            self.regions[self.len] = region;
+            self.len += 1;
+        }
+    }
+
+    pub fn regions(&self) -> &[MemoryRegion] {
+        &self.regions[..self.len]
+    }
+
+    pub fn usable_bytes(&self) -> u64 {
+        self.regions()
+            .iter()
+            .filter(|region| region.kind == MemoryKind::Usable)
+            .map(|region| region.end - region.start)
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameBufferInfo {
+    pub addr: u64,
+    pub width: usize,
+    pub height: usize,
+    pub stride: usize,
+    pub bytes_per_pixel: usize,
+}
+
+/// Everything the kernel wants from the bootloader, whichever protocol
+/// booted us. Anything the protocol doesn't provide is left empty.
+// Nothing reads the framebuffer, command line, or RSDP yet.
+#[allow(dead_code)]
+pub struct BootInfoCommon {
+    pub memory_map: MemoryMap,
+    pub framebuffer: Option<FrameBufferInfo>,
+    pub command_line: Option<&'static str>,
+    pub rsdp_addr: Option<u64>,
+    /// Where all of physical memory is mapped in the virtual address space.
+    pub physical_memory_offset: Option<u64>,
+}
+
+/// Define a new entrypoint for Gumbo OS that calls $main with the
+/// BootInfoCommon for the bios protocol.
+///
+/// no_mangle: Prevents rust from generating a unique id for the function
+/// extern "C": Ensures we use the C calling conventions
+/// It also shouldn't return, and it will rely on an exit system call the OS will implement
+#[cfg(feature = "bios")]
+#[macro_export]
+macro_rules! kernel_entry {
+    ($main:path) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn _start() -> ! {
+            $main($crate::arch::entry::from_bios())
+        }
+    };
+}
+
+/// Define a new entrypoint for Gumbo OS that calls $main with the
+/// BootInfoCommon translated from bootloader 0.9's BootInfo.
+#[cfg(feature = "bootloader")]
+#[macro_export]
+macro_rules! kernel_entry {
+    ($main:path) => {
+        // entry_point! type checks our signature and exports it as _start.
+        $crate::arch::entry::bootloader::entry_point!(__kernel_entry);
+
+        fn __kernel_entry(boot_info: &'static $crate::arch::entry::bootloader::BootInfo) -> ! {
+            $main($crate::arch::entry::from_bootloader(boot_info))
+        }
+    };
+}
+
+#[cfg(feature = "bootloader")]
+#[doc(hidden)]
+pub use bootloader;
+
+/// The bios protocol tells us nothing, so everything is left empty.
+#[cfg(feature = "bios")]
+#[doc(hidden)]
+pub fn from_bios() -> BootInfoCommon {
+    crate::early_println!("Gumbo OS: entered _start (bios)");
+
+    BootInfoCommon {
+        memory_map: MemoryMap::empty(),
+        framebuffer: None,
+        command_line: None,
+        rsdp_addr: None,
+        physical_memory_offset: None,
+    }
+}
+
+/// bootloader 0.9 reports the memory map and, with map_physical_memory,
+/// where physical memory is mapped. It boots in VGA text mode and has no
+/// command line or RSDP to pass along.
+#[cfg(feature = "bootloader")]
+#[doc(hidden)]
+pub fn from_bootloader(boot_info: &'static bootloader::BootInfo) -> BootInfoCommon {
+    use bootloader::bootinfo::MemoryRegionType;
+
+    crate::early_println!("Gumbo OS: entered _start (bootloader)");
+
+    let mut memory_map = MemoryMap::empty();
+    for region in boot_info.memory_map.iter() {
+        memory_map.push(MemoryRegion {
+            start: region.range.start_addr(),
+            end: region.range.end_addr(),
+            kind: match region.region_type {
+                MemoryRegionType::Usable => MemoryKind::Usable,
+                _ => MemoryKind::Reserved,
+            },
+        });
+    }
+
+    BootInfoCommon {
+        memory_map,
+        framebuffer: None,
+        command_line: None,
+        rsdp_addr: None,
+        physical_memory_offset: Some(boot_info.physical_memory_offset),
+    }
+}
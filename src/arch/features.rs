@@ -0,0 +1,133 @@
+//! Checking the CPU has the features the kernel relies on
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::arch::x86_64::{
+    __cpuid,
+    CpuidResult,
+};
+
+const EXTENDED_MAX_LEAF: u32 = 0x8000_0000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    Ecx,
+    Edx,
+}
+
+/// A feature is one bit of one CPUID leaf.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feature {
+    pub name: &'static str,
+    pub leaf: u32,
+    pub register: Register,
+    pub bit: u32,
+}
+
+impl Feature {
+    const fn new(name: &'static str, leaf: u32, register: Register, bit: u32) -> Self {
+        Self { name, leaf, register, bit }
+    }
+
+    fn present(&self, cpuid: &impl Fn(u32) -> CpuidResult) -> bool {
+        // Leaves past the maximum return garbage rather than zeroes.
+        let max_leaf = cpuid(self.leaf & EXTENDED_MAX_LEAF).eax;
+        if self.leaf > max_leaf {
+            return false;
+        }
+
+        let leaf = cpuid(self.leaf);
+        let value = match self.register {
+            Register::Ecx => leaf.ecx,
+            Register::Edx => leaf.edx,
+        };
+        value & (1 << self.bit) != 0
+    }
+}
+
+pub const LONG_MODE: Feature = Feature::new("long mode", 0x8000_0001, Register::Edx, 29);
+/// memory::protect relies on this for W^X.
+pub const NX: Feature = Feature::new("NX", 0x8000_0001, Register::Edx, 20);
+pub const SSE: Feature = Feature::new("SSE", 1, Register::Edx, 25);
+pub const SSE2: Feature = Feature::new("SSE2", 1, Register::Edx, 26);
+pub const APIC: Feature = Feature::new("APIC", 1, Register::Edx, 9);
+pub const X2APIC: Feature = Feature::new("x2APIC", 1, Register::Ecx, 21);
+pub const XSAVE: Feature = Feature::new("XSAVE", 1, Register::Ecx, 26);
+pub const AVX: Feature = Feature::new("AVX", 1, Register::Ecx, 28);
+
+/// Without any of these we stop at boot.
+pub const REQUIRED: [Feature; 4] = [LONG_MODE, NX, SSE, SSE2];
+
+/// Subsystems that want one of these check has_feature and skip
+/// themselves if it's missing.
+pub const OPTIONAL: [Feature; 4] = [APIC, X2APIC, XSAVE, AVX];
+
+pub fn has_feature(feature: &Feature) -> bool {
+    feature.present(&__cpuid)
+}
+
+/// The first required feature cpuid doesn't report. Takes cpuid as a
+/// parameter so the check can run against made-up results.
+pub fn first_missing(cpuid: impl Fn(u32) -> CpuidResult) -> Option<Feature> {
+    REQUIRED.into_iter().find(|feature| !feature.present(&cpuid))
+}
+
+/// Run first thing, before anything uses the features, so an old CPU
+/// gets a clear message instead of a fault deep in init. The message goes
+/// to serial as well, since a headless machine has no screen to read.
+///
+/// Missing optional features are normal, so they only get one line on
+/// serial for whoever is debugging, not the screen.
+pub fn verify_requirements() {
+    if let Some(feature) = first_missing(__cpuid) {
+        crate::early_println!("unsupported CPU: missing {}", feature.name);
+        crate::println!("unsupported CPU: missing {}", feature.name);
+        super::halt();
+    }
+
+    let mut missing = OPTIONAL.iter().filter(|feature| !has_feature(feature)).peekable();
+    if missing.peek().is_some() {
+        crate::early_print!("CPU: continuing without");
+        for feature in missing {
+            crate::early_print!(" {}", feature.name);
+        }
+        crate::early_println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A CPU that reports every leaf up to 0x8000_0001 with all feature
+    /// bits set, except for the bits in missing_extended_edx.
+    fn stub_cpuid(leaf: u32, missing_extended_edx: u32) -> CpuidResult {
+        let (eax, edx) = match leaf {
+            0 => (1, 0),
+            EXTENDED_MAX_LEAF => (0x8000_0001, 0),
+            0x8000_0001 => (0, !missing_extended_edx),
+            _ => (0, u32::MAX),
+        };
+        CpuidResult { eax, ebx: 0, ecx: u32::MAX, edx }
+    }
+
+    #[test_case]
+    fn reports_nothing_when_all_present() {
+        assert_eq!(first_missing(|leaf| stub_cpuid(leaf, 0)), None);
+    }
+
+    #[test_case]
+    fn reports_missing_nx() {
+        assert_eq!(first_missing(|leaf| stub_cpuid(leaf, 1 << NX.bit)), Some(NX));
+    }
+
+    #[test_case]
+    fn leaves_past_the_maximum_count_as_missing() {
+        let cpuid = |leaf| match leaf {
+            EXTENDED_MAX_LEAF => CpuidResult { eax: EXTENDED_MAX_LEAF, ebx: 0, ecx: 0, edx: 0 },
+            _ => stub_cpuid(leaf, 0),
+        };
+        assert_eq!(first_missing(cpuid), Some(LONG_MODE));
+    }
+}
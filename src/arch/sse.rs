@@ -0,0 +1,91 @@
+//! Turning on the FPU and SSE so SIMD instructions don't fault
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::arch::asm;
+
+use super::features::{
+    self,
+    AVX,
+    XSAVE,
+};
+
+/// x87 emulation. While set, every FPU or SSE instruction raises #NM.
+const CR0_EM: u64 = 1 << 2;
+/// Monitor coprocessor, so wait/fwait also honour CR0.TS.
+const CR0_MP: u64 = 1 << 1;
+/// We save SSE state with fxsave/fxrstor, and SSE stays #UD without it.
+const CR4_OSFXSR: u64 = 1 << 9;
+/// SIMD floating point exceptions raise #XM instead of #UD.
+const CR4_OSXMMEXCPT: u64 = 1 << 10;
+/// Allows xgetbv/xsetbv, and with them AVX.
+const CR4_OSXSAVE: u64 = 1 << 18;
+
+/// XCR0 bits for the x87, SSE, and AVX state components. AVX can only be
+/// enabled together with the other two.
+const XCR0_X87: u64 = 1 << 0;
+const XCR0_SSE: u64 = 1 << 1;
+const XCR0_AVX: u64 = 1 << 2;
+
+/// Enable the FPU and SSE, and AVX if the CPU has it. Requires SSE,
+/// which verify_requirements has already checked.
+///
+/// The target spec still builds the kernel with soft-float, so the
+/// compiler won't emit SSE on its own. Letting it do so would mean saving
+/// the XMM registers on every interrupt, which waits on the interrupt
+/// entry code. Until then this is for code that uses SIMD explicitly.
+pub fn enable_sse() {
+    unsafe {
+        let mut cr0: u64;
+        asm!("mov {}, cr0", out(reg) cr0, options(nomem, nostack, preserves_flags));
+        cr0 = (cr0 & !CR0_EM) | CR0_MP;
+        asm!("mov cr0, {}", in(reg) cr0, options(nostack, preserves_flags));
+
+        let mut cr4: u64;
+        asm!("mov {}, cr4", out(reg) cr4, options(nomem, nostack, preserves_flags));
+        cr4 |= CR4_OSFXSR | CR4_OSXMMEXCPT;
+        let avx = features::has_feature(&XSAVE) && features::has_feature(&AVX);
+        if avx {
+            cr4 |= CR4_OSXSAVE;
+        }
+        asm!("mov cr4, {}", in(reg) cr4, options(nostack, preserves_flags));
+
+        if avx {
+            let xcr0 = XCR0_X87 | XCR0_SSE | XCR0_AVX;
+            asm!(
+                "xsetbv",
+                in("ecx") 0,
+                in("eax") xcr0 as u32,
+                in("edx") (xcr0 >> 32) as u32,
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn movdqu_round_trips_through_xmm0() {
+        let source: [u8; 16] = core::array::from_fn(|i| i as u8 * 17);
+        let mut destination = [0u8; 16];
+
+        // Soft-float code never touches xmm0, so it's free to clobber
+        // without declaring it, which the target features wouldn't allow.
+        // Faults with #UD if enable_sse hasn't run.
+        unsafe {
+            asm!(
+                "movdqu xmm0, [{source}]",
+                "movdqu [{destination}], xmm0",
+                source = in(reg) source.as_ptr(),
+                destination = in(reg) destination.as_mut_ptr(),
+                options(nostack, preserves_flags),
+            )
+        };
+
+        assert_eq!(destination, source);
+    }
+}
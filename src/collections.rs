@@ -0,0 +1,9 @@
+//! Data structures that work without a heap
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+mod bitmap;
+
+#[allow(unused_imports)]
+pub use bitmap::BitMap;
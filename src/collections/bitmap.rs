@@ -0,0 +1,165 @@
+//! A fixed-size bitmap for tracking frames and other numbered resources
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Bit i lives in word i / 64 at position i % 64. A set bit means the
+/// resource is in use.
+///
+/// The words are any storage that derefs to [u64]. BitMap::<[u64; N]>::new
+/// is const, so a bitmap can be a static before the heap exists. Once
+/// the size is only known at runtime, from_words wraps a slice carved out
+/// elsewhere (say from early_alloc), and a Box<[u64]> will work the same
+/// way once there is a heap.
+pub struct BitMap<S> {
+    words: S,
+}
+
+impl<const N: usize> BitMap<[u64; N]> {
+    pub const fn new() -> Self {
+        Self { words: [0; N] }
+    }
+}
+
+impl<const N: usize> Default for BitMap<[u64; N]> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> BitMap<&'a mut [u64]> {
+    /// Wrap existing words, keeping whatever bits are already set.
+    pub fn from_words(words: &'a mut [u64]) -> Self {
+        Self { words }
+    }
+}
+
+impl<S: AsRef<[u64]> + AsMut<[u64]>> BitMap<S> {
+    /// Number of bits, always a multiple of 64.
+    pub fn len(&self) -> usize {
+        self.words.as_ref().len() * WORD_BITS
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Panics if index is out of range, like a slice.
+    pub fn get(&self, index: usize) -> bool {
+        self.words.as_ref()[index / WORD_BITS] & bit(index) != 0
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.words.as_mut()[index / WORD_BITS] |= bit(index);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        self.words.as_mut()[index / WORD_BITS] &= !bit(index);
+    }
+
+    pub fn find_first_clear(&self) -> Option<usize> {
+        self.words.as_ref()
+            .iter()
+            .enumerate()
+            .find(|&(_, &word)| word != u64::MAX)
+            .map(|(i, word)| i * WORD_BITS + word.trailing_ones() as usize)
+    }
+
+    /// The first index starting len clear bits in a row, e.g. len
+    /// contiguous frames.
+    ///
+    /// Runs of set or clear bits are skipped a word at a time with
+    /// trailing_ones/trailing_zeros, so a full word costs one step
+    /// rather than 64.
+    pub fn find_first_clear_run(&self, len: usize) -> Option<usize> {
+        if len == 0 {
+            return Some(0);
+        }
+
+        let words = self.words.as_ref();
+        let mut start = 0;
+        let mut index = 0;
+        while index < self.len() {
+            let offset = index % WORD_BITS;
+            let rest = words[index / WORD_BITS] >> offset;
+            let left_in_word = WORD_BITS - offset;
+
+            let ones = (rest.trailing_ones() as usize).min(left_in_word);
+            if ones > 0 {
+                index += ones;
+                start = index;
+                continue;
+            }
+
+            // Shifting brings in zeros at the top, which min(left_in_word)
+            // keeps from counting as clear bits.
+            index += (rest.trailing_zeros() as usize).min(left_in_word);
+            if index - start >= len {
+                return Some(start);
+            }
+        }
+        None
+    }
+}
+
+const fn bit(index: usize) -> u64 {
+    1 << (index % WORD_BITS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn find_first_clear_after_pattern() {
+        let mut bitmap = BitMap::<[u64; 3]>::new();
+        assert_eq!(bitmap.find_first_clear(), Some(0));
+
+        for i in 0..70 {
+            bitmap.set(i);
+        }
+        assert_eq!(bitmap.find_first_clear(), Some(70));
+
+        bitmap.clear(3);
+        assert!(!bitmap.get(3));
+        assert_eq!(bitmap.find_first_clear(), Some(3));
+
+        for i in 0..bitmap.len() {
+            bitmap.set(i);
+        }
+        assert_eq!(bitmap.find_first_clear(), None);
+    }
+
+    #[test_case]
+    fn clear_runs_cross_word_boundaries() {
+        let mut bitmap = BitMap::<[u64; 3]>::new();
+        for i in 0..70 {
+            bitmap.set(i);
+        }
+        bitmap.set(100);
+
+        // 70..100 is clear, and so is 101 to the end at 192.
+        assert_eq!(bitmap.find_first_clear_run(30), Some(70));
+        assert_eq!(bitmap.find_first_clear_run(31), Some(101));
+        assert_eq!(bitmap.find_first_clear_run(192 - 101), Some(101));
+        assert_eq!(bitmap.find_first_clear_run(192 - 100), None);
+
+        // A one-bit hole is found first, but too short for a pair.
+        bitmap.clear(3);
+        assert_eq!(bitmap.find_first_clear_run(1), Some(3));
+        assert_eq!(bitmap.find_first_clear_run(2), Some(70));
+    }
+
+    #[test_case]
+    fn clear_runs_skip_full_and_partial_words() {
+        // Word 0 full, word 1 clear except its top bit, word 2 clear.
+        let mut words = [u64::MAX, 1 << 63, 0];
+        let bitmap = BitMap::from_words(&mut words);
+        assert_eq!(bitmap.find_first_clear_run(63), Some(64));
+        assert_eq!(bitmap.find_first_clear_run(64), Some(128));
+        assert_eq!(bitmap.find_first_clear_run(65), None);
+        assert_eq!(bitmap.find_first_clear_run(0), Some(0));
+    }
+}
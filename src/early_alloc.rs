@@ -0,0 +1,97 @@
+//! A bump allocator for the window before the heap exists
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    alloc::Layout,
+    cell::UnsafeCell,
+    ptr::NonNull,
+    sync::atomic::{
+        AtomicBool,
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+/// Enough for a few tables during init. Anything bigger should wait for
+/// the heap.
+const ARENA_SIZE: usize = 64 * 1024;
+
+/// Page aligned so a page-sized request wastes nothing on padding.
+#[repr(C, align(4096))]
+struct Arena(UnsafeCell<[u8; ARENA_SIZE]>);
+
+// Every byte is handed out at most once, so no two users share one.
+unsafe impl Sync for Arena {}
+
+static ARENA: Arena = Arena(UnsafeCell::new([0; ARENA_SIZE]));
+
+/// Offset of the first free byte.
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+static CLOSED: AtomicBool = AtomicBool::new(false);
+
+/// Hand out a chunk of the arena. Nothing is ever freed, so memory from
+/// here lives for the rest of the kernel's life. Returns None once the
+/// arena can't fit the request.
+///
+/// Panics after close: by then the real allocator is up and anything
+/// still calling this is a bug.
+pub fn alloc(layout: Layout) -> Option<NonNull<u8>> {
+    if CLOSED.load(Ordering::Acquire) {
+        panic!("early_alloc used after the heap took over ({} bytes)", layout.size());
+    }
+
+    let base = ARENA.0.get() as usize;
+    let mut next = NEXT.load(Ordering::Relaxed);
+    loop {
+        // Align the address rather than the offset, in case a request
+        // asks for more than the arena's own alignment.
+        let start = (base + next).checked_next_multiple_of(layout.align())? - base;
+        let end = start.checked_add(layout.size())?;
+        if end > ARENA_SIZE {
+            return None;
+        }
+
+        match NEXT.compare_exchange_weak(next, end, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return NonNull::new((base + start) as *mut u8),
+            Err(actual) => next = actual,
+        }
+    }
+}
+
+/// Called once the real allocator takes over.
+pub fn close() {
+    CLOSED.store(true, Ordering::Release);
+}
+
+/// Bytes handed out so far, including alignment padding.
+pub fn used() -> usize {
+    NEXT.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn over_aligned_request_is_aligned() {
+        // Push NEXT off the arena's own page alignment first.
+        alloc(Layout::from_size_align(1, 1).unwrap()).unwrap();
+
+        let layout = Layout::from_size_align(16, 8192).unwrap();
+        let chunk = alloc(layout).unwrap();
+        assert!((chunk.as_ptr() as usize).is_multiple_of(8192));
+        assert!(chunk.as_ptr() as usize + 16 <= ARENA.0.get() as usize + used());
+    }
+
+    // Leaves nothing for anyone after it, so it stays the last test here.
+    #[test_case]
+    fn none_once_the_arena_is_used_up() {
+        let rest = ARENA_SIZE - used();
+        assert!(alloc(Layout::from_size_align(rest + 1, 1).unwrap()).is_none());
+        assert!(alloc(Layout::from_size_align(rest, 1).unwrap()).is_some());
+        assert_eq!(used(), ARENA_SIZE);
+        assert!(alloc(Layout::from_size_align(1, 1).unwrap()).is_none());
+    }
+}
@@ -0,0 +1,120 @@
+//! Serial output that works from the first instruction of _start
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    fmt,
+    sync::atomic::{
+        AtomicU8,
+        Ordering,
+    },
+};
+
+use crate::port;
+
+/// COM1's registers, as offsets from its base port.
+const COM1: u16 = 0x3f8;
+const DATA: u16 = 0;
+const INTERRUPT_ENABLE: u16 = 1;
+const FIFO_CONTROL: u16 = 2;
+const LINE_CONTROL: u16 = 3;
+const MODEM_CONTROL: u16 = 4;
+const LINE_STATUS: u16 = 5;
+
+/// While set in LINE_CONTROL, DATA and INTERRUPT_ENABLE hold the baud
+/// rate divisor instead.
+const DLAB: u8 = 1 << 7;
+/// 8 data bits, no parity, one stop bit.
+const LINE_8N1: u8 = 0x03;
+/// 115200 / 3 = 38400 baud.
+const DIVISOR: u16 = 3;
+/// Enable and clear both FIFOs, interrupting at 14 bytes.
+const FIFO_ENABLE: u8 = 0xc7;
+/// DTR and RTS, so the other end knows we're there.
+const MODEM_READY: u8 = 0x03;
+/// The transmit holding register can take another byte.
+const TRANSMIT_EMPTY: u8 = 1 << 5;
+
+const UNINIT: u8 = 0;
+const INITIALIZING: u8 = 1;
+const READY: u8 = 2;
+
+/// There is no lock, only this flag to program the UART before the first
+/// byte. Whoever moves it from UNINIT runs init, and everyone else waits
+/// for READY. Otherwise a byte sent while the divisor latch is still
+/// selected would change the baud rate instead of being transmitted.
+static STATE: AtomicU8 = AtomicU8::new(UNINIT);
+
+fn init() {
+    unsafe {
+        port::outb(COM1 + INTERRUPT_ENABLE, 0);
+        port::outb(COM1 + LINE_CONTROL, DLAB);
+        port::outb(COM1 + DATA, DIVISOR as u8);
+        port::outb(COM1 + INTERRUPT_ENABLE, (DIVISOR >> 8) as u8);
+        port::outb(COM1 + LINE_CONTROL, LINE_8N1);
+        port::outb(COM1 + FIFO_CONTROL, FIFO_ENABLE);
+        port::outb(COM1 + MODEM_CONTROL, MODEM_READY);
+    }
+}
+
+pub fn write_byte(byte: u8) {
+    if STATE.load(Ordering::Acquire) != READY {
+        if STATE.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Acquire).is_ok() {
+            init();
+            STATE.store(READY, Ordering::Release);
+        } else {
+            while STATE.load(Ordering::Acquire) != READY {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    unsafe {
+        while port::inb(COM1 + LINE_STATUS) & TRANSMIT_EMPTY == 0 {
+            core::hint::spin_loop();
+        }
+        port::outb(COM1 + DATA, byte);
+    }
+}
+
+/// Write a plain string with no core::fmt involved, for paths like a
+/// double panic where formatting itself may be what broke.
+pub fn write_str(s: &str) {
+    for byte in s.bytes() {
+        // Terminals expect CRLF.
+        if byte == b'\n' {
+            write_byte(b'\r');
+        }
+        write_byte(byte);
+    }
+}
+
+/// Lets early_print! use format_args without any shared writer.
+pub struct EarlySerial;
+
+impl fmt::Write for EarlySerial {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        write_str(s);
+        Ok(())
+    }
+}
+
+/// Print to COM1 with no setup and no lock. Meant for the stretch before
+/// the VGA console works, and for debugging it.
+#[macro_export]
+macro_rules! early_print {
+    ($($arg:tt)*) => ($crate::early_serial::_print(format_args!($($arg)*)));
+}
+
+#[macro_export]
+macro_rules! early_println {
+    () => ($crate::early_print!("\n"));
+    ($($arg:tt)*) => ($crate::early_print!("{}\n", format_args!($($arg)*)));
+}
+
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments) {
+    use core::fmt::Write;
+    let _ = EarlySerial.write_fmt(args);
+}
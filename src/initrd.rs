@@ -0,0 +1,227 @@
+//! A read-only filesystem over a USTAR archive bundled with the kernel
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+/// A USTAR archive is a sequence of 512 byte blocks. Each file is a
+/// header block followed by its data, padded up to a whole block:
+/// ==  Bytes  ==|== Field ==
+///    0-99      |   Name
+///    124-135   |   Size (octal ASCII)
+///    148-155   |   Checksum (octal ASCII)
+///    156       |   Type flag
+///    257-262   |   Magic "ustar"
+///    345-499   |   Name prefix
+///
+/// The archive ends with two all-zero blocks.
+const BLOCK_SIZE: usize = 512;
+
+const NAME: core::ops::Range<usize> = 0..100;
+const SIZE: core::ops::Range<usize> = 124..136;
+const CHECKSUM: core::ops::Range<usize> = 148..156;
+const TYPE_FLAG: usize = 156;
+const MAGIC: core::ops::Range<usize> = 257..262;
+const PREFIX: core::ops::Range<usize> = 345..500;
+
+/// Older archivers write a NUL type flag for regular files.
+const TYPE_REGULAR: u8 = b'0';
+const TYPE_REGULAR_OLD: u8 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitrdError {
+    /// The archive ended in the middle of a header or a file's data.
+    Truncated { offset: usize },
+    /// The header does not carry the "ustar" magic.
+    BadMagic { offset: usize },
+    /// The stored checksum does not match the header bytes.
+    BadChecksum { offset: usize },
+    /// A numeric field is not valid octal or the name is not UTF-8.
+    BadField { offset: usize },
+    /// The entry uses the prefix field for paths over 100 bytes.
+    LongPath { offset: usize },
+}
+
+struct Entry<'a> {
+    name: &'a str,
+    type_flag: u8,
+    data: &'a [u8],
+}
+
+impl Entry<'_> {
+    fn is_file(&self) -> bool {
+        self.type_flag == TYPE_REGULAR || self.type_flag == TYPE_REGULAR_OLD
+    }
+}
+
+/// Walks the archive one header at a time. Every header has already
+/// been checked by Initrd::new, so this never fails.
+struct Entries<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Entry<'a>;
+
+    fn next(&mut self) -> Option<Entry<'a>> {
+        match parse_header(self.data, self.offset) {
+            Ok(Some((entry, next))) => {
+                self.offset = next;
+                Some(entry)
+            }
+            _ => None,
+        }
+    }
+}
+
+pub struct Initrd<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Initrd<'a> {
+    /// Check every header in the archive up front so lookups can't
+    /// trip over a corrupt entry later.
+    pub fn new(data: &'a [u8]) -> Result<Self, InitrdError> {
+        let mut offset = 0;
+        while let Some((_, next)) = parse_header(data, offset)? {
+            offset = next;
+        }
+
+        Ok(Self { data })
+    }
+
+    /// Returns the contents of the regular file at path, if any.
+    /// A leading "/" or "./" on either side is ignored.
+    pub fn open(&self, path: &str) -> Option<&'a [u8]> {
+        let path = normalize(path);
+        self.entries()
+            .find(|entry| entry.is_file() && normalize(entry.name) == path)
+            .map(|entry| entry.data)
+    }
+
+    /// Names of all regular files in archive order.
+    pub fn list(&self) -> impl Iterator<Item = &'a str> {
+        self.entries()
+            .filter(|entry| entry.is_file())
+            .map(|entry| normalize(entry.name))
+    }
+
+    fn entries(&self) -> Entries<'a> {
+        Entries { data: self.data, offset: 0 }
+    }
+}
+
+/// Parse the header at offset, returning the entry and the offset of the
+/// next header, or None once the end-of-archive marker is reached.
+fn parse_header(data: &[u8], offset: usize) -> Result<Option<(Entry<'_>, usize)>, InitrdError> {
+    // Some archivers stop without the trailing zero blocks, so running
+    // out of data exactly on a block boundary also ends the archive.
+    if offset == data.len() {
+        return Ok(None);
+    }
+
+    let header = data.get(offset..offset + BLOCK_SIZE).ok_or(InitrdError::Truncated { offset })?;
+    if header.iter().all(|&byte| byte == 0) {
+        return Ok(None);
+    }
+
+    if &header[MAGIC] != b"ustar" {
+        return Err(InitrdError::BadMagic { offset });
+    }
+
+    let stored = parse_octal(&header[CHECKSUM]).ok_or(InitrdError::BadField { offset })?;
+    if stored != checksum(header) {
+        return Err(InitrdError::BadChecksum { offset });
+    }
+
+    if header[PREFIX][0] != 0 {
+        return Err(InitrdError::LongPath { offset });
+    }
+
+    let name = until_nul(&header[NAME]);
+    let name = core::str::from_utf8(name).map_err(|_| InitrdError::BadField { offset })?;
+    let size = parse_octal(&header[SIZE]).ok_or(InitrdError::BadField { offset })? as usize;
+
+    let start = offset + BLOCK_SIZE;
+    let data = data.get(start..start + size).ok_or(InitrdError::Truncated { offset })?;
+    let next = start + size.div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+
+    Ok(Some((Entry { name, type_flag: header[TYPE_FLAG], data }, next)))
+}
+
+/// The checksum is the sum of every header byte, counting the checksum
+/// field itself as if it were filled with spaces.
+fn checksum(header: &[u8]) -> u64 {
+    header.iter()
+        .enumerate()
+        .map(|(i, &byte)| if CHECKSUM.contains(&i) { b' ' as u64 } else { byte as u64 })
+        .sum()
+}
+
+/// Numeric fields are octal ASCII, optionally padded with leading spaces
+/// and terminated by a NUL or a space.
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let mut digits = field.iter().skip_while(|&&byte| byte == b' ').peekable();
+    digits.peek()?;
+
+    let mut value: u64 = 0;
+    for &byte in digits {
+        match byte {
+            b'0'..=b'7' => value = value.checked_mul(8)? + (byte - b'0') as u64,
+            0 | b' ' => break,
+            _ => return None,
+        }
+    }
+
+    Some(value)
+}
+
+fn until_nul(field: &[u8]) -> &[u8] {
+    let len = field.iter().position(|&byte| byte == 0).unwrap_or(field.len());
+    &field[..len]
+}
+
+fn normalize(path: &str) -> &str {
+    path.trim_start_matches("./").trim_start_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// hello.txt and dir/data.bin (600 bytes, so it spans two blocks),
+    /// written by Python's tarfile in USTAR format.
+    static ARCHIVE: &[u8; 3584] = include_bytes!("../tests/fixtures/initrd.tar");
+
+    #[test_case]
+    fn open_reads_both_files() {
+        let initrd = Initrd::new(ARCHIVE).unwrap();
+        assert_eq!(initrd.open("hello.txt"), Some(&b"Hello, Gumbo!\n"[..]));
+
+        let data = initrd.open("/dir/data.bin").unwrap();
+        assert_eq!(data.len(), 600);
+        assert!(data.iter().enumerate().all(|(i, &byte)| byte == (i % 251) as u8));
+
+        assert_eq!(initrd.open("missing.txt"), None);
+    }
+
+    #[test_case]
+    fn list_names_files_in_order() {
+        let initrd = Initrd::new(ARCHIVE).unwrap();
+        let mut names = initrd.list();
+        assert_eq!(names.next(), Some("hello.txt"));
+        assert_eq!(names.next(), Some("dir/data.bin"));
+        assert_eq!(names.next(), None);
+    }
+
+    #[test_case]
+    fn corrupt_header_is_rejected() {
+        let mut archive = *ARCHIVE;
+        archive[NAME.start] ^= 1;
+        assert!(matches!(Initrd::new(&archive), Err(InitrdError::BadChecksum { offset: 0 })));
+
+        let mut archive = *ARCHIVE;
+        archive[MAGIC.start] = b'x';
+        assert!(matches!(Initrd::new(&archive), Err(InitrdError::BadMagic { offset: 0 })));
+    }
+}
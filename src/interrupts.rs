@@ -0,0 +1,80 @@
+//! Helpers for querying and controlling the CPU interrupt flag
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    arch::asm,
+    marker::PhantomData,
+};
+
+/// Bit 9 of RFLAGS is the interrupt flag (IF).
+const RFLAGS_IF: u64 = 1 << 9;
+
+pub fn are_enabled() -> bool {
+    let rflags: u64;
+    unsafe { asm!("pushfq", "pop {}", out(reg) rflags, options(nomem, preserves_flags)) };
+    rflags & RFLAGS_IF != 0
+}
+
+/// These deliberately don't claim nomem, so the compiler treats them as
+/// barriers and can't move memory accesses out of the region they guard.
+pub fn enable() {
+    unsafe { asm!("sti", options(nostack)) };
+}
+
+pub fn disable() {
+    unsafe { asm!("cli", options(nostack)) };
+}
+
+/// Put IF back to what are_enabled returned earlier. Restoring rather
+/// than always enabling is what lets interrupt-disabling scopes nest:
+/// only the outermost one turns interrupts back on.
+pub fn restore(were_enabled: bool) {
+    if were_enabled {
+        enable();
+    }
+}
+
+/// Interrupts stay off for as long as this is alive.
+///
+/// IF is per core, so the guard is neither Send nor Sync: dropping it on
+/// another core would restore the wrong core's flag.
+pub struct InterruptGuard {
+    were_enabled: bool,
+    not_send: PhantomData<*const ()>,
+}
+
+/// Disable interrupts until the returned guard is dropped. This nests
+/// with other guards and with IrqSafeSpinLock's guard.
+#[allow(dead_code)]
+pub fn guard() -> InterruptGuard {
+    let were_enabled = are_enabled();
+    disable();
+    InterruptGuard { were_enabled, not_send: PhantomData }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        restore(self.were_enabled);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::with_interrupts_enabled;
+
+    #[test_case]
+    fn nested_guards_restore_if_when_the_outer_one_drops() {
+        with_interrupts_enabled(|| {
+            let outer_guard = guard();
+            assert!(!are_enabled());
+            let inner_guard = guard();
+            drop(inner_guard);
+            assert!(!are_enabled());
+            drop(outer_guard);
+            assert!(are_enabled());
+        });
+    }
+}
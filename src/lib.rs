@@ -0,0 +1,73 @@
+//! Gumbo OS as a library, so the kernel binary and the integration tests
+//! under tests/ build on the same code, following
+//! https://os.phil-opp.com/testing/#integration-tests
+//!
+//! `cargo test` builds this crate into a test kernel of its own that runs
+//! every #[test_case] in it.
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+#![no_std]
+#![cfg_attr(test, no_main)]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::testing::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+use crate::arch::entry::BootInfoCommon;
+
+pub mod arch;
+#[cfg(feature = "bench")]
+pub mod bench;
+// Nothing tracks frames yet.
+#[allow(dead_code)]
+mod collections;
+pub mod early_alloc;
+pub mod early_serial;
+mod interrupts;
+mod memory;
+mod mmio;
+mod port;
+// Nothing reprograms the palette yet.
+#[allow(dead_code)]
+mod palette;
+pub mod panicking;
+// Nothing powers the machine off yet.
+#[allow(dead_code)]
+mod power;
+pub mod vga_buffer;
+mod spinlock;
+pub mod sync;
+pub mod testing;
+pub mod util;
+// Nothing hands us an archive until the bootloader can load modules.
+#[allow(dead_code)]
+mod initrd;
+
+/// Get the CPU and memory into the state the rest of the kernel expects.
+/// Every kernel built from this crate calls it first thing.
+pub fn init(boot_info: &BootInfoCommon) {
+    arch::verify_requirements();
+    arch::enable_sse();
+
+    if let Some(offset) = boot_info.physical_memory_offset {
+        memory::set_physical_memory_offset(offset);
+        unsafe { memory::protect::protect(offset) };
+    }
+}
+
+#[cfg(test)]
+kernel_entry!(test_kernel_main);
+
+#[cfg(test)]
+fn test_kernel_main(boot_info: BootInfoCommon) -> ! {
+    init(&boot_info);
+    test_main();
+    arch::halt()
+}
+
+#[cfg(test)]
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    testing::panic(info)
+}
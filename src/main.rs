@@ -1,4 +1,8 @@
-#![no_std] #![no_main] /// no_std: Don't include the standard library
+#![no_std] #![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(gumbo_os::testing::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+/// no_std: Don't include the standard library
 ///
 /// The kernel will not have access to the std library binary
 /// because it has operating system constructs for networking,
@@ -14,34 +18,81 @@
 /// The following site was used as a reference: https://os.phil-opp.com/
 use core::panic::PanicInfo;
 
-use crate::{
-    spinlock::SpinLockGuard,
-    vga_buffer::{WRITER, Writer},
+use gumbo_os::{
+    arch::{
+        self,
+        entry::BootInfoCommon,
+    },
+    println,
+    util,
+    vga_buffer::{WRITER, Color},
 };
 
-mod vga_buffer;
-mod spinlock;
+gumbo_os::kernel_entry!(kernel_main);
 
-static GREETING: &str = "Welcome to Gumbo OS!";
+static LOGO: &str = r"
+  ____                 _              ___  ____
+ / ___|_   _ _ __ ___ | |__   ___    / _ \/ ___|
+| |  _| | | | '_ ` _ \| '_ \ / _ \  | | | \___ \
+| |_| | |_| | | | | | | |_) | (_) | | |_| |___) |
+ \____|\__,_|_| |_| |_|_.__/ \___/   \___/|____/
+";
+
+static VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Print the boot banner: the logo, the kernel version, and what we
+/// know about the machine so far.
+fn print_banner(boot_info: &BootInfoCommon) {
+    WRITER.acquire().set_color(Color::Yellow, Color::Black);
+    println!("{}", LOGO);
+
+    WRITER.acquire().set_color(Color::LightCyan, Color::Black);
+    println!("Welcome to Gumbo OS v{}!", VERSION);
+
+    let vendor = arch::cpu_vendor();
+    WRITER.acquire().set_color(Color::White, Color::Black);
+    println!("CPU: {}", core::str::from_utf8(&vendor).unwrap_or("unknown"));
+    match boot_info.memory_map.usable_bytes() {
+        0 => println!("Memory: unknown"),
+        bytes => println!("Memory: {} usable", util::HumanBytes(bytes)),
+    }
+
+    WRITER.acquire().set_color(Color::Yellow, Color::Black);
+}
 
 /// Reimplement the panic handler
 ///
 /// Normally the Rust std library defines the panic handler for us.
 /// However, since the kernel will not have the std library at start up,
 /// we will need to define our own versions of std library features.
+///
+/// panicking::report does the printing, and has to cope with a panic
+/// from inside itself.
+#[cfg(not(test))]
 #[panic_handler]
-fn panic(_info: &PanicInfo) -> ! {
-    println!("{}", _info); 
-    loop {}
+fn panic(info: &PanicInfo) -> ! {
+    gumbo_os::panicking::report(info);
+    arch::halt()
 }
 
-/// Define a new entrypoint for Gumbo OS
-///
-/// no_mangle: Prevents rust from generating a unique id for the function
-/// extern "C": Ensures we use the C calling conventions
-/// It also shouldn't return, and it will rely on an exit system call the OS will implement
-#[unsafe(no_mangle)]
-pub extern "C" fn _start() -> ! {
-    println!("{}", GREETING); 
-    loop {}
+#[cfg(test)]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    gumbo_os::testing::panic(info)
+}
+
+/// Where every boot protocol ends up once arch::entry has translated
+/// what the bootloader gave us.
+fn kernel_main(boot_info: BootInfoCommon) -> ! {
+    gumbo_os::init(&boot_info);
+    print_banner(&boot_info);
+
+    #[cfg(feature = "bench")]
+    gumbo_os::bench::run_all();
+
+    #[cfg(test)]
+    test_main();
+
+    // Nothing is left to do and no interrupt will bring us any work.
+    arch::halt()
 }
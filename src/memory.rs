@@ -0,0 +1,26 @@
+//! Physical and virtual memory management
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+pub mod image;
+pub mod paging;
+pub mod protect;
+
+use crate::sync::OnceCell;
+
+static PHYSICAL_MEMORY_OFFSET: OnceCell<u64> = OnceCell::new();
+
+/// Where all of physical memory is mapped, if the bootloader told us.
+/// Set once by init, before anything walks the page tables.
+// Only tests walk the page tables on their own yet.
+#[allow(dead_code)]
+pub fn physical_memory_offset() -> Option<u64> {
+    PHYSICAL_MEMORY_OFFSET.get().copied()
+}
+
+pub fn set_physical_memory_offset(offset: u64) {
+    if PHYSICAL_MEMORY_OFFSET.set(offset).is_err() {
+        panic!("physical memory offset set twice");
+    }
+}
@@ -0,0 +1,85 @@
+//! The kernel's own ELF segments, read from the headers loaded with it
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    ops::Range,
+    slice,
+};
+
+/// p_type of a segment the bootloader loads.
+const PT_LOAD: u32 = 1;
+/// p_flags permission bits.
+const PF_X: u32 = 1 << 0;
+const PF_W: u32 = 1 << 1;
+
+unsafe extern "C" {
+    /// The start of the ELF file header. The linker defines this whenever
+    /// the first segment starts at file offset 0 and so carries the
+    /// headers, which it does for the kernel, and bootloader 0.9 maps that
+    /// segment like any other.
+    static __ehdr_start: ElfHeader;
+}
+
+/// The start of the ELF64 file header, up to the program header count.
+// Laid out as in the ELF spec, so most fields are only there for padding.
+#[allow(dead_code)]
+#[repr(C)]
+struct ElfHeader {
+    ident: [u8; 16],
+    kind: u16,
+    machine: u16,
+    version: u32,
+    entry: u64,
+    phoff: u64,
+    shoff: u64,
+    flags: u32,
+    ehsize: u16,
+    phentsize: u16,
+    phnum: u16,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct ProgramHeader {
+    kind: u32,
+    flags: u32,
+    offset: u64,
+    vaddr: u64,
+    paddr: u64,
+    filesz: u64,
+    memsz: u64,
+    align: u64,
+}
+
+/// A loaded segment of the kernel image and the access the linker gave
+/// it. Readable is implied, every loaded segment is.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub range: Range<u64>,
+    pub writable: bool,
+    pub executable: bool,
+}
+
+/// Every segment of the running kernel, in the order they're laid out.
+/// The linker starts each one on a page of its own, so changing the
+/// permissions of one never touches another.
+pub fn segments() -> impl Iterator<Item = Segment> {
+    let header = unsafe { &__ehdr_start };
+    assert_eq!(&header.ident[..4], b"\x7fELF", "no ELF header at __ehdr_start");
+    assert_eq!(header.phentsize as usize, size_of::<ProgramHeader>());
+
+    // phoff counts from the start of the file, which is where the header is.
+    let first = (&raw const __ehdr_start as usize + header.phoff as usize) as *const ProgramHeader;
+    let program_headers = unsafe { slice::from_raw_parts(first, header.phnum as usize) };
+
+    program_headers
+        .iter()
+        .filter(|program_header| program_header.kind == PT_LOAD)
+        .map(|program_header| Segment {
+            range: program_header.vaddr..program_header.vaddr + program_header.memsz,
+            writable: program_header.flags & PF_W != 0,
+            executable: program_header.flags & PF_X != 0,
+        })
+}
@@ -0,0 +1,317 @@
+//! Inspecting the active x86_64 page tables
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    arch::asm,
+    ops::Range,
+};
+
+/// Each level of the table has 512 entries. A virtual address is split
+/// into four 9 bit indices, one per level, plus a 12 bit page offset:
+/// ==  Bits  ==|== Level ==
+///    39-47    |  PML4 (level 4)
+///    30-38    |  PDPT (level 3), may map a 1 GiB page
+///    21-29    |  PD   (level 2), may map a 2 MiB page
+///    12-20    |  PT   (level 1), maps a 4 KiB page
+const ENTRIES: usize = 512;
+const LEVELS: usize = 4;
+
+/// Bytes covered by one entry at each level, top level first.
+const ENTRY_SIZE: [u64; LEVELS] = [1 << 39, 1 << 30, 1 << 21, 1 << 12];
+
+const PRESENT: u64 = 1;
+pub const WRITABLE: u64 = 1 << 1;
+const USER: u64 = 1 << 2;
+const HUGE_PAGE: u64 = 1 << 7;
+const GLOBAL: u64 = 1 << 8;
+pub const NO_EXECUTE: u64 = 1 << 63;
+
+/// Bits 12-51 of an entry hold the physical address of the next table or
+/// of the frame it maps.
+pub const ADDRESS_MASK: u64 = 0x000f_ffff_ffff_f000;
+
+/// The permissions that actually apply to a page. An access is only
+/// allowed if every level allows it, so these are combined from all the
+/// entries on the way down rather than read from the last one alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageFlags {
+    pub writable: bool,
+    pub user: bool,
+    pub no_execute: bool,
+    pub global: bool,
+}
+
+impl PageFlags {
+    const TOP: Self = Self { writable: true, user: true, no_execute: false, global: false };
+
+    fn through(self, entry: u64) -> Self {
+        Self {
+            writable: self.writable && entry & WRITABLE != 0,
+            user: self.user && entry & USER != 0,
+            no_execute: self.no_execute || entry & NO_EXECUTE != 0,
+            // Only meaningful on the entry that maps the page.
+            global: entry & GLOBAL != 0,
+        }
+    }
+}
+
+/// A run of virtually and physically contiguous memory mapped with the
+/// same effective flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mapping {
+    pub virt_start: u64,
+    pub phys_start: u64,
+    pub size: u64,
+    pub flags: PageFlags,
+}
+
+impl Mapping {
+    #[allow(dead_code)]
+    pub fn contains(&self, addr: u64) -> bool {
+        (self.virt_start..self.virt_start + self.size).contains(&addr)
+    }
+
+    fn extends(&self, next: &Mapping) -> bool {
+        self.flags == next.flags
+            && self.virt_start.wrapping_add(self.size) == next.virt_start
+            && self.phys_start.wrapping_add(self.size) == next.phys_start
+    }
+}
+
+/// Walks every present leaf entry (a 4 KiB page or a huge page) in
+/// address order, one mapped page at a time.
+struct Leaves {
+    phys_offset: u64,
+    level_4_table: u64,
+    indices: [usize; LEVELS],
+}
+
+impl Leaves {
+    fn entry(&self, table: u64, index: usize) -> u64 {
+        let table = (self.phys_offset + table) as *const u64;
+        unsafe { table.add(index).read_volatile() }
+    }
+
+    /// Sign extend bit 47 so the upper half comes out canonical.
+    fn virt_addr(&self, level: usize) -> u64 {
+        let addr = (0..=level)
+            .map(|l| self.indices[l] as u64 * ENTRY_SIZE[l])
+            .sum::<u64>();
+        canonical(addr)
+    }
+
+    /// Move past the entry at level, skipping everything beneath it.
+    fn advance(&mut self, level: usize) {
+        self.indices[level + 1..].fill(0);
+        self.indices[level] += 1;
+
+        let mut level = level;
+        while level > 0 && self.indices[level] == ENTRIES {
+            self.indices[level] = 0;
+            level -= 1;
+            self.indices[level] += 1;
+        }
+    }
+}
+
+impl Iterator for Leaves {
+    type Item = Mapping;
+
+    fn next(&mut self) -> Option<Mapping> {
+        'walk: while self.indices[0] < ENTRIES {
+            let mut table = self.level_4_table;
+            let mut flags = PageFlags::TOP;
+
+            for (level, &size) in ENTRY_SIZE.iter().enumerate() {
+                let entry = self.entry(table, self.indices[level]);
+                if entry & PRESENT == 0 {
+                    self.advance(level);
+                    continue 'walk;
+                }
+
+                flags = flags.through(entry);
+
+                // Level 4 entries can't map pages directly, and the PT
+                // level always does.
+                let is_leaf = level == LEVELS - 1 || (level > 0 && entry & HUGE_PAGE != 0);
+                if is_leaf {
+                    let mapping = Mapping {
+                        virt_start: self.virt_addr(level),
+                        phys_start: entry & ADDRESS_MASK & !(size - 1),
+                        size,
+                        flags,
+                    };
+                    self.advance(level);
+                    return Some(mapping);
+                }
+
+                table = entry & ADDRESS_MASK;
+            }
+        }
+
+        None
+    }
+}
+
+/// Merges neighbouring leaves into larger mappings.
+struct Coalesced {
+    leaves: Leaves,
+    pending: Option<Mapping>,
+}
+
+impl Iterator for Coalesced {
+    type Item = Mapping;
+
+    fn next(&mut self) -> Option<Mapping> {
+        for leaf in self.leaves.by_ref() {
+            match self.pending {
+                Some(ref mut run) if run.extends(&leaf) => run.size += leaf.size,
+                Some(run) => {
+                    self.pending = Some(leaf);
+                    return Some(run);
+                }
+                None => self.pending = Some(leaf),
+            }
+        }
+
+        self.pending.take()
+    }
+}
+
+fn canonical(addr: u64) -> u64 {
+    ((addr << 16) as i64 >> 16) as u64
+}
+
+/// The physical address of the active level 4 table, from CR3.
+pub fn active_level_4_table() -> u64 {
+    let cr3: u64;
+    unsafe { asm!("mov {}, cr3", out(reg) cr3, options(nomem, nostack, preserves_flags)) };
+    cr3 & ADDRESS_MASK
+}
+
+/// Iterate over everything mapped in the active address space, merging
+/// contiguous runs with identical flags. Huge pages come out as a single
+/// larger mapping.
+///
+/// Safety: all physical memory must be mapped at phys_offset, as
+/// bootloader's map_physical_memory feature does, and the page tables
+/// must not change while the iterator is in use.
+pub unsafe fn iter_mappings(phys_offset: u64) -> impl Iterator<Item = Mapping> {
+    Coalesced {
+        leaves: Leaves {
+            phys_offset,
+            level_4_table: active_level_4_table(),
+            indices: [0; LEVELS],
+        },
+        pending: None,
+    }
+}
+
+/// Reload CR3 with its current value, which flushes every non-global
+/// TLB entry.
+pub fn flush_all() {
+    unsafe {
+        asm!(
+            "mov {tmp}, cr3",
+            "mov cr3, {tmp}",
+            tmp = out(reg) _,
+            options(nostack, preserves_flags),
+        )
+    };
+}
+
+/// The address ranges of any level 4 slots that point back at the level 4
+/// table itself, i.e. a recursive mapping like the one bootloader 0.9
+/// installs.
+///
+/// Safety: same as iter_mappings.
+pub unsafe fn recursive_slots(phys_offset: u64) -> impl Iterator<Item = Range<u64>> {
+    let level_4_table = active_level_4_table();
+    let table = (phys_offset + level_4_table) as *const u64;
+
+    (0..ENTRIES)
+        .filter(move |&index| {
+            let entry = unsafe { table.add(index).read_volatile() };
+            entry & PRESENT != 0 && entry & ADDRESS_MASK == level_4_table
+        })
+        .map(|index| {
+            let start = canonical(index as u64 * ENTRY_SIZE[0]);
+            start..start + ENTRY_SIZE[0]
+        })
+}
+
+/// Rewrite the entries that map range (rounded out to whole 4 KiB pages)
+/// with f, then flush the TLB.
+///
+/// At each level, an entry whose whole span lies inside the range is
+/// rewritten itself, which covers everything beneath it. Otherwise we go
+/// down a level. A huge page that only partly overlaps the range is
+/// rewritten in full, since it can't be split here.
+///
+/// Safety: same as iter_mappings. It is also up to the caller not to take
+/// away access the kernel still needs. Never pass a range inside a
+/// recursive slot unless the range covers the whole slot: its "pages" are
+/// the page tables themselves, so rewriting them would change unrelated
+/// mappings.
+pub unsafe fn update_entries(phys_offset: u64, range: Range<u64>, f: impl Fn(u64) -> u64) {
+    let start = range.start & !0xfff;
+    let last = range.end.saturating_sub(1) | 0xfff;
+    let mut addr = start;
+
+    'walk: while range.start < range.end && addr <= last {
+        let mut table = active_level_4_table();
+
+        for (level, &size) in ENTRY_SIZE.iter().enumerate() {
+            let index = ((addr / size) % ENTRIES as u64) as usize;
+            let entry_ptr = unsafe { ((phys_offset + table) as *mut u64).add(index) };
+            let entry = unsafe { entry_ptr.read_volatile() };
+
+            let span_start = addr & !(size - 1);
+            let covered = span_start >= start && span_start + (size - 1) <= last;
+            let is_leaf = level == LEVELS - 1 || (level > 0 && entry & HUGE_PAGE != 0);
+
+            if entry & PRESENT == 0 || covered || is_leaf {
+                if entry & PRESENT != 0 {
+                    unsafe { entry_ptr.write_volatile(f(entry)) };
+                }
+
+                match span_start.checked_add(size) {
+                    Some(next) => addr = next,
+                    None => break 'walk,
+                }
+                continue 'walk;
+            }
+
+            table = entry & ADDRESS_MASK;
+        }
+    }
+
+    flush_all();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory;
+
+    fn mapping_at(addr: u64) -> Option<Mapping> {
+        let phys_offset = memory::physical_memory_offset().unwrap();
+        unsafe { iter_mappings(phys_offset) }.find(|mapping| mapping.contains(addr))
+    }
+
+    #[test_case]
+    fn boot_mappings_include_vga_and_kernel_image() {
+        let vga = mapping_at(0xb8000).expect("VGA text buffer not mapped");
+        assert!(vga.flags.writable);
+
+        // The kernel is linked at 2 MiB, headers and read-only data first.
+        let image_start = mapping_at(0x20_0000).expect("kernel image not mapped");
+        assert!(!image_start.flags.writable);
+
+        let text = mapping_at(mapping_at as *const () as u64).expect("kernel code not mapped");
+        assert!(!text.flags.writable);
+        assert!(!text.flags.no_execute);
+    }
+}
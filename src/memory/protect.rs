@@ -0,0 +1,147 @@
+//! Enforcing that no kernel page is both writable and executable (W^X)
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::ops::Range;
+
+use crate::arch;
+
+use super::{
+    image,
+    paging::{self, Mapping},
+};
+
+const IA32_EFER: u32 = 0xc000_0080;
+/// Without this bit the CPU ignores NX and every page is executable.
+const EFER_NXE: u64 = 1 << 11;
+
+/// Upper bound on fix-ups, in case a mapping refuses to change.
+const MAX_FIXUPS: usize = 64;
+
+pub fn enable_nx() {
+    unsafe {
+        let efer = arch::read_msr(IA32_EFER);
+        arch::write_msr(IA32_EFER, efer | EFER_NXE);
+    }
+}
+
+/// Safety: see paging::update_entries.
+pub unsafe fn set_nx(phys_offset: u64, range: Range<u64>) {
+    unsafe { paging::update_entries(phys_offset, range, |entry| entry | paging::NO_EXECUTE) };
+}
+
+/// Safety: see paging::update_entries.
+pub unsafe fn set_readonly(phys_offset: u64, range: Range<u64>) {
+    unsafe { paging::update_entries(phys_offset, range, |entry| entry & !paging::WRITABLE) };
+}
+
+fn is_wx(mapping: &Mapping) -> bool {
+    mapping.flags.writable && !mapping.flags.no_execute
+}
+
+/// The first mapping that is both writable and executable, if any.
+///
+/// Safety: see paging::iter_mappings.
+pub unsafe fn find_wx(phys_offset: u64) -> Option<Mapping> {
+    unsafe { paging::iter_mappings(phys_offset) }.find(is_wx)
+}
+
+/// Panic with the first mapping that is both writable and executable.
+///
+/// Safety: see paging::iter_mappings.
+pub unsafe fn check_wx(phys_offset: u64) {
+    if let Some(mapping) = unsafe { find_wx(phys_offset) } {
+        panic!(
+            "W+X mapping at {:#x}..{:#x} (phys {:#x})",
+            mapping.virt_start,
+            mapping.virt_start + mapping.size,
+            mapping.phys_start,
+        );
+    }
+}
+
+/// Give each of the kernel's segments the access its ELF flags ask for:
+/// read-only, read and execute, or read-write without execute. A segment
+/// that asks for write and execute is a bug in the kernel image, not
+/// something to fix up here.
+///
+/// Safety: see paging::update_entries.
+unsafe fn protect_kernel_image(phys_offset: u64) {
+    for segment in image::segments() {
+        let range = segment.range;
+        match (segment.writable, segment.executable) {
+            (true, true) => panic!("kernel segment {:#x}..{:#x} is writable and executable", range.start, range.end),
+            (true, false) => unsafe { set_nx(phys_offset, range) },
+            (false, true) => unsafe { set_readonly(phys_offset, range) },
+            (false, false) => unsafe {
+                set_readonly(phys_offset, range.clone());
+                set_nx(phys_offset, range);
+            },
+        }
+    }
+}
+
+/// Run once after init.
+///
+/// The kernel's segments get their permissions from the ELF headers, see
+/// protect_kernel_image. After that, whatever is still writable and
+/// executable is one of bootloader 0.9's own mappings: the low identity
+/// map, the recursive slot, the boot info page, the stack, or the
+/// physical memory window. None of those are ever executed, so they all
+/// lose execute, and the final check makes sure nothing is left.
+///
+/// Safety: see paging::iter_mappings.
+pub unsafe fn protect(phys_offset: u64) {
+    enable_nx();
+
+    // The recursive slot has to go first and as a whole. Its "pages" are
+    // the page tables, so fixing them one by one would rewrite real entries.
+    for slot in unsafe { paging::recursive_slots(phys_offset) } {
+        unsafe { set_nx(phys_offset, slot) };
+    }
+
+    unsafe { protect_kernel_image(phys_offset) };
+
+    for _ in 0..MAX_FIXUPS {
+        match unsafe { find_wx(phys_offset) } {
+            Some(mapping) => unsafe { set_nx(phys_offset, mapping.virt_start..mapping.virt_start + mapping.size) },
+            None => break,
+        }
+    }
+
+    unsafe { check_wx(phys_offset) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory;
+
+    /// A page of .bss to make W+X on purpose. It has to be mutable, or it
+    /// would land in read-only data.
+    #[repr(C, align(4096))]
+    struct Page([u8; 4096]);
+
+    static mut SCRATCH: Page = Page([0; 4096]);
+
+    #[test_case]
+    fn find_wx_reports_a_wx_page() {
+        let phys_offset = memory::physical_memory_offset().unwrap();
+        let addr = &raw const SCRATCH as u64;
+        let page = addr..addr + 4096;
+
+        unsafe {
+            assert_eq!(find_wx(phys_offset), None);
+
+            paging::update_entries(phys_offset, page.clone(), |entry| entry & !paging::NO_EXECUTE);
+            let found = find_wx(phys_offset);
+            paging::update_entries(phys_offset, page, |entry| entry | paging::NO_EXECUTE);
+
+            let mapping = found.expect("W+X page not reported");
+            assert!(mapping.contains(addr));
+            assert_eq!(mapping.size, 4096);
+            assert_eq!(find_wx(phys_offset), None);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Volatile access to memory-mapped device registers
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    marker::PhantomData,
+    ops::{
+        BitAnd,
+        BitOr,
+        Not,
+    },
+    ptr,
+};
+
+/// A device register at a fixed address.
+///
+/// Devices have side effects the compiler can't see, so every access is
+/// a read_volatile or write_volatile that can't be merged or dropped.
+/// The unsafe part, vouching that the address really is the register,
+/// happens once in new instead of at every cast.
+pub struct Mmio<T> {
+    addr: usize,
+    register: PhantomData<*mut T>,
+}
+
+impl<T> Clone for Mmio<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Mmio<T> {}
+
+impl<T> Mmio<T> {
+    /// Panics on a misaligned address. A constant built with new, like
+    /// `const REG: Mmio<u32> = unsafe { Mmio::new(0xfee0_00b0) };`, turns
+    /// that into a compile error.
+    ///
+    /// Safety: addr must be mapped and hold a T for as long as this is used.
+    pub const unsafe fn new(addr: usize) -> Self {
+        assert!(addr.is_multiple_of(align_of::<T>()), "misaligned MMIO address");
+        Self { addr, register: PhantomData }
+    }
+
+    /// For a register block laid out as a struct of Volatile fields, like
+    /// the VGA text buffer.
+    ///
+    /// Safety: there must be no other reference to the block.
+    pub unsafe fn as_mut(self) -> &'static mut T {
+        unsafe { &mut *(self.addr as *mut T) }
+    }
+}
+
+impl<T: Copy> Mmio<T> {
+    #[allow(dead_code)]
+    pub fn read(&self) -> T {
+        unsafe { ptr::read_volatile(self.addr as *const T) }
+    }
+
+    #[allow(dead_code)]
+    pub fn write(&self, value: T) {
+        unsafe { ptr::write_volatile(self.addr as *mut T, value) };
+    }
+
+    /// Read, change, and write back. This is two separate accesses, so
+    /// it's up to the caller to keep anyone else off the register.
+    #[allow(dead_code)]
+    pub fn modify(&self, f: impl FnOnce(T) -> T) {
+        self.write(f(self.read()));
+    }
+}
+
+/// Helpers for registers made of flag bits and fields.
+impl<T> Mmio<T>
+where
+    T: Copy + PartialEq + BitAnd<Output = T> + BitOr<Output = T> + Not<Output = T>,
+{
+    #[allow(dead_code)]
+    pub fn set_bits(&self, mask: T) {
+        self.modify(|value| value | mask);
+    }
+
+    #[allow(dead_code)]
+    pub fn clear_bits(&self, mask: T) {
+        self.modify(|value| value & !mask);
+    }
+
+    /// Whether every bit in mask is set.
+    #[allow(dead_code)]
+    pub fn bits_set(&self, mask: T) -> bool {
+        self.read() & mask == mask
+    }
+}
+
+/// A field inside a memory-mapped struct that is only ever accessed
+/// volatilely. It has the same size and alignment as T, so a struct of
+/// these matches the device layout exactly.
+#[repr(transparent)]
+pub struct Volatile<T: Copy>(T);
+
+impl<T: Copy> Volatile<T> {
+    pub fn read(&self) -> T {
+        unsafe { ptr::read_volatile(&self.0) }
+    }
+
+    pub fn write(&mut self, value: T) {
+        unsafe { ptr::write_volatile(&mut self.0, value) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn round_trips_through_a_local() {
+        let mut backing = 0u32;
+        let register = unsafe { Mmio::<u32>::new(&raw mut backing as usize) };
+
+        register.write(0x1234_5678);
+        assert_eq!(register.read(), 0x1234_5678);
+
+        register.set_bits(0x8000_0001);
+        assert_eq!(register.read(), 0x9234_5679);
+        assert!(register.bits_set(0x8000_0001));
+
+        register.clear_bits(0x1200_0008);
+        assert_eq!(register.read(), 0x8034_5671);
+        assert!(!register.bits_set(0x1000_0000));
+
+        assert_eq!(backing, 0x8034_5671);
+    }
+}
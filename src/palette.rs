@@ -0,0 +1,87 @@
+//! The 16 text-mode colors as RGB, and access to the VGA DAC palette
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use crate::{port, vga_buffer::Color};
+
+/// The canonical RGB value of each text color, indexed by Color as u8.
+/// Anything drawing in graphics mode can use this to match the text console.
+pub const PALETTE: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00), // Black
+    (0x00, 0x00, 0xaa), // Blue
+    (0x00, 0xaa, 0x00), // Green
+    (0x00, 0xaa, 0xaa), // Cyan
+    (0xaa, 0x00, 0x00), // Red
+    (0xaa, 0x00, 0xaa), // Magenta
+    (0xaa, 0x55, 0x00), // Brown
+    (0xaa, 0xaa, 0xaa), // LightGray
+    (0x55, 0x55, 0x55), // DarkGray
+    (0x55, 0x55, 0xff), // LightBlue
+    (0x55, 0xff, 0x55), // LightGreen
+    (0x55, 0xff, 0xff), // LightCyan
+    (0xff, 0x55, 0x55), // LightRed
+    (0xff, 0x55, 0xff), // Pink
+    (0xff, 0xff, 0x55), // Yellow
+    (0xff, 0xff, 0xff), // White
+];
+
+pub const fn to_rgb(color: Color) -> (u8, u8, u8) {
+    PALETTE[color as usize]
+}
+
+/// In text mode a color goes through the attribute controller before it
+/// reaches the DAC, and the default mapping isn't the identity: Brown uses
+/// DAC entry 20 and the bright colors use 56-63. Indexed by Color as u8.
+pub const TEXT_DAC_INDEX: [u8; 16] = [
+    0, 1, 2, 3, 4, 5, 20, 7,
+    56, 57, 58, 59, 60, 61, 62, 63,
+];
+
+const DAC_READ_INDEX: u16 = 0x3c7;
+const DAC_WRITE_INDEX: u16 = 0x3c8;
+const DAC_DATA: u16 = 0x3c9;
+
+/// Set DAC entry index to the given 8 bit color. The DAC only keeps 6
+/// bits per component, so the low two bits are dropped.
+pub fn vga_set_palette(index: u8, r: u8, g: u8, b: u8) {
+    unsafe {
+        port::outb(DAC_WRITE_INDEX, index);
+        // Writing the three components in order commits the entry.
+        port::outb(DAC_DATA, r >> 2);
+        port::outb(DAC_DATA, g >> 2);
+        port::outb(DAC_DATA, b >> 2);
+    }
+}
+
+/// Read DAC entry index back, scaling the 6 bit components up to 8 bits.
+pub fn vga_get_palette(index: u8) -> (u8, u8, u8) {
+    let widen = |c: u8| (c << 2) | (c >> 4);
+    unsafe {
+        port::outb(DAC_READ_INDEX, index);
+        let r = port::inb(DAC_DATA);
+        let g = port::inb(DAC_DATA);
+        let b = port::inb(DAC_DATA);
+        (widen(r & 0x3f), widen(g & 0x3f), widen(b & 0x3f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn set_palette_reads_back() {
+        let index = TEXT_DAC_INDEX[Color::White as usize];
+        let original = vga_get_palette(index);
+
+        // Only the top six bits stick, and reading widens them back out
+        // by repeating the top two in the bottom.
+        vga_set_palette(index, 0x12, 0x80, 0xfc);
+        assert_eq!(vga_get_palette(index), (0x10, 0x82, 0xff));
+
+        let (r, g, b) = original;
+        vga_set_palette(index, r, g, b);
+        assert_eq!(vga_get_palette(index), original);
+    }
+}
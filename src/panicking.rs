@@ -0,0 +1,57 @@
+//! The part of panicking that every panic handler in the kernel shares
+//!
+//! The kernel binary and the tests under tests/ each define their own
+//! #[panic_handler], and they all report through here so a double panic
+//! behaves the same in a test as it does for real.
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    fmt::Write,
+    panic::PanicInfo,
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use crate::{
+    early_println,
+    early_serial,
+    sync::Lazy,
+    try_println,
+    vga_buffer::WRITER,
+};
+
+/// How many panics are in progress, see report.
+static PANIC_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Print what we can about a panic and return how many panics were
+/// already in progress, so the caller knows how deep it is.
+///
+/// A panic while panicking would recurse until the stack overflows, so
+/// PANIC_DEPTH counts how deep we are and each level does less:
+/// - The first panic prints the full message. It goes to serial first,
+///   which never blocks, then to the screen unless the panic struck
+///   while the writer was held or still being set up, since waiting on
+///   either would hang.
+/// - A panic from inside that prints a fixed line without core::fmt.
+/// - Anything deeper returns without trying to print at all.
+pub fn report(info: &PanicInfo) -> usize {
+    let depth = PANIC_DEPTH.fetch_add(1, Ordering::SeqCst);
+    match depth {
+        0 => {
+            early_println!("{}", info);
+            if let Some(mut writer) = Lazy::get(&WRITER).and_then(|writer| writer.try_acquire()) {
+                let _ = writeln!(writer, "{}", info);
+            }
+        }
+        1 => {
+            early_serial::write_str("double panic, halting\n");
+            try_println!("double panic, halting");
+        }
+        _ => {}
+    }
+    depth
+}
@@ -0,0 +1,131 @@
+//! Wrappers around the x86 in/out instructions for talking to I/O ports
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    arch::asm,
+    marker::PhantomData,
+};
+
+/// Writing to an arbitrary port can reconfigure hardware out from under
+/// the kernel, so callers must know what lives at the port.
+pub unsafe fn outw(port: u16, value: u16) {
+    unsafe { asm!("out dx, ax", in("dx") port, in("ax") value, options(nomem, nostack, preserves_flags)) };
+}
+
+pub unsafe fn outb(port: u16, value: u8) {
+    unsafe { asm!("out dx, al", in("dx") port, in("al") value, options(nomem, nostack, preserves_flags)) };
+}
+
+pub unsafe fn outl(port: u16, value: u32) {
+    unsafe { asm!("out dx, eax", in("dx") port, in("eax") value, options(nomem, nostack, preserves_flags)) };
+}
+
+/// Reading can also have side effects, e.g. popping a byte from a FIFO.
+pub unsafe fn inb(port: u16) -> u8 {
+    let value: u8;
+    unsafe { asm!("in al, dx", out("al") value, in("dx") port, options(nomem, nostack, preserves_flags)) };
+    value
+}
+
+pub unsafe fn inw(port: u16) -> u16 {
+    let value: u16;
+    unsafe { asm!("in ax, dx", out("ax") value, in("dx") port, options(nomem, nostack, preserves_flags)) };
+    value
+}
+
+pub unsafe fn inl(port: u16) -> u32 {
+    let value: u32;
+    unsafe { asm!("in eax, dx", out("eax") value, in("dx") port, options(nomem, nostack, preserves_flags)) };
+    value
+}
+
+/// The widths the in/out instructions come in. Ports generally expect
+/// one particular width, e.g. PCI configuration data is 32 bits.
+pub trait PortAccess: Copy {
+    unsafe fn read_from(port: u16) -> Self;
+    unsafe fn write_to(port: u16, value: Self);
+}
+
+impl PortAccess for u8 {
+    unsafe fn read_from(port: u16) -> u8 {
+        unsafe { inb(port) }
+    }
+
+    unsafe fn write_to(port: u16, value: u8) {
+        unsafe { outb(port, value) }
+    }
+}
+
+impl PortAccess for u16 {
+    unsafe fn read_from(port: u16) -> u16 {
+        unsafe { inw(port) }
+    }
+
+    unsafe fn write_to(port: u16, value: u16) {
+        unsafe { outw(port, value) }
+    }
+}
+
+impl PortAccess for u32 {
+    unsafe fn read_from(port: u16) -> u32 {
+        unsafe { inl(port) }
+    }
+
+    unsafe fn write_to(port: u16, value: u32) {
+        unsafe { outl(port, value) }
+    }
+}
+
+/// A port with its width fixed by the type, so the right instruction is
+/// picked for every access. As with Mmio, the unsafe promise about what
+/// lives at the port is made once, in new.
+#[derive(Debug, Clone, Copy)]
+pub struct Port<T: PortAccess> {
+    port: u16,
+    width: PhantomData<T>,
+}
+
+impl<T: PortAccess> Port<T> {
+    /// Safety: port must be one that is accessed with T's width, and
+    /// where reads and writes don't break invariants the kernel relies on.
+    #[allow(dead_code)]
+    pub const unsafe fn new(port: u16) -> Self {
+        Self { port, width: PhantomData }
+    }
+
+    #[allow(dead_code)]
+    pub fn read(&self) -> T {
+        unsafe { T::read_from(self.port) }
+    }
+
+    #[allow(dead_code)]
+    pub fn write(&self, value: T) {
+        unsafe { T::write_to(self.port, value) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PCI_CONFIG_ADDRESS: u16 = 0xcf8;
+    const PCI_CONFIG_DATA: u16 = 0xcfc;
+
+    #[test_case]
+    fn pci_config_reads_agree_across_widths() {
+        // Enable bit, bus 0, device 0, function 0, register 0: the host
+        // bridge's vendor and device ID, which every PC has.
+        unsafe { Port::<u32>::new(PCI_CONFIG_ADDRESS) }.write(0x8000_0000);
+
+        let dword = unsafe { Port::<u32>::new(PCI_CONFIG_DATA) }.read();
+        let mut bytes = [0u8; 4];
+        for (offset, byte) in (0..).zip(&mut bytes) {
+            *byte = unsafe { Port::<u8>::new(PCI_CONFIG_DATA + offset) }.read();
+        }
+
+        assert_ne!(dword, u32::MAX, "no host bridge answered");
+        assert_eq!(u32::from_le_bytes(bytes), dword);
+    }
+}
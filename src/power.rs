@@ -0,0 +1,42 @@
+//! Powering the machine off through the ACPI sleep registers
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use crate::{arch, interrupts, port};
+
+/// Entering a sleep state means writing SLP_TYP (bits 10-12) and SLP_EN
+/// (bit 13) to the PM1a control block. The real port and the S5 SLP_TYP
+/// come from the FADT and the DSDT's _S5 object, but reading them needs
+/// the ACPI tables mapped and an AML parser. Until then we use the values
+/// the common emulators are known to expose.
+const SLP_EN: u16 = 1 << 13;
+
+const fn pm1_control(slp_typ: u16) -> u16 {
+    slp_typ << 10 | SLP_EN
+}
+
+/// (PM1a control port, S5 SLP_TYP) pairs to try, in order.
+const KNOWN_PM1A_CONTROL: [(u16, u16); 3] = [
+    // QEMU q35 and recent i440fx machines
+    (0x604, 0),
+    // Bochs and older QEMU
+    (0xb004, 0),
+    // VirtualBox
+    (0x4004, 5),
+];
+
+/// Ask the machine to enter S5 (soft off). If none of the known ports
+/// power us off, halt forever instead.
+pub fn shutdown() -> ! {
+    interrupts::disable();
+
+    for (port, slp_typ) in KNOWN_PM1A_CONTROL {
+        // On real hardware these ports may belong to something else, but
+        // we are about to stop running either way.
+        unsafe { port::outw(port, pm1_control(slp_typ)) };
+    }
+
+    crate::println!("shutdown failed, halting");
+    arch::halt()
+}
@@ -4,18 +4,46 @@
 /// Date: Sun. Jan 18, 2026
 
 use core::{
+    marker::PhantomData,
     ops::{
         Deref,
         DerefMut,
     },
     sync::atomic::{
-        AtomicBool,
+        AtomicUsize,
         Ordering,
     },
     cell::UnsafeCell,
     hint,
 };
 
+use crate::interrupts;
+
+/// How many times acquire polls a held lock before backing off.
+///
+/// Below the scheduler there is nothing to back off to, so for now it
+/// just keeps spinning. A hlt would be tempting, but releasing a lock
+/// sends no interrupt, and with no timer ticking a halted waiter could
+/// sleep forever after the lock is free. Once the scheduler exists this
+/// is where yield_now() goes.
+// Nothing yields yet.
+#[allow(dead_code)]
+const SPIN_YIELD_THRESHOLD: usize = 1024;
+
+/// A strategy for taking and releasing a lock through a single state word.
+///
+/// Lock owns the state word and the data, and LockGuard owns the Deref
+/// machinery, so a policy only has to say how the word is used. The token
+/// is whatever the policy needs to carry from lock to unlock.
+pub trait LockPolicy {
+    type Token: Copy;
+    const INIT: usize;
+    fn lock(state: &AtomicUsize) -> Self::Token;
+    /// Take the lock only if that can be done without waiting.
+    fn try_lock(state: &AtomicUsize) -> Option<Self::Token>;
+    fn unlock(state: &AtomicUsize, token: Self::Token);
+}
+
 /// The following spinlock is a test and test-and-set (TTAS) lock.
 ///
 /// Standard test-and-set locks suffers from cache contention.
@@ -29,63 +57,303 @@ use core::{
 ///
 /// Refer here for more info: https://rigtorp.se/spinlock/
 #[derive(Debug, Default)]
-pub struct SpinLock<T> {
-    flag: AtomicBool,
+pub struct TtasPolicy;
+
+const UNLOCKED: usize = 0;
+const LOCKED: usize = 1;
+
+impl LockPolicy for TtasPolicy {
+    type Token = ();
+    const INIT: usize = UNLOCKED;
+
+    /// Memory ordering:
+    /// - The polling load is Relaxed. It only decides when to try the CAS
+    ///   and never publishes or consumes the protected data.
+    /// - A successful CAS is Acquire, pairing with the Release store in
+    ///   unlock, so everything the previous holder wrote is visible to us.
+    /// - A failed CAS is Relaxed, since we learn nothing from it but "held".
+    ///
+    /// The flag only ever flips between UNLOCKED and LOCKED, and the CAS
+    /// just asks whether it is UNLOCKED right now, so there is no ABA hazard.
+    fn lock(state: &AtomicUsize) {
+        loop {
+            // Test before test-and-set: an already held lock is spun on with
+            // plain reads, so we never issue the cache-line-stealing CAS
+            // until we have actually seen the lock free.
+            while state.load(Ordering::Relaxed) == LOCKED {
+                hint::spin_loop();
+            }
+
+            if state.compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                return;
+            }
+        }
+    }
+
+    fn try_lock(state: &AtomicUsize) -> Option<()> {
+        if state.load(Ordering::Relaxed) == LOCKED {
+            return None;
+        }
+        state.compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed).ok().map(|_| ())
+    }
+
+    fn unlock(state: &AtomicUsize, _token: ()) {
+        state.store(UNLOCKED, Ordering::Release);
+    }
+}
+
+/// A ticket lock hands out the lock in the order cores asked for it,
+/// so no core can be starved by faster ones repeatedly winning the CAS.
+///
+/// The upper half of the state word is the next ticket to hand out and
+/// the lower half is the ticket currently being served.
+#[derive(Debug, Default)]
+pub struct TicketPolicy;
+
+const TICKET_SHIFT: u32 = usize::BITS / 2;
+const SERVING_MASK: usize = (1 << TICKET_SHIFT) - 1;
+
+impl LockPolicy for TicketPolicy {
+    type Token = ();
+    const INIT: usize = 0;
+
+    fn lock(state: &AtomicUsize) {
+        // Adding to the upper half wraps around on overflow without
+        // touching the lower half, so both counters wrap independently.
+        let ticket = (state.fetch_add(1 << TICKET_SHIFT, Ordering::Relaxed) >> TICKET_SHIFT) & SERVING_MASK;
+
+        while state.load(Ordering::Acquire) & SERVING_MASK != ticket {
+            hint::spin_loop();
+        }
+    }
+
+    fn try_lock(state: &AtomicUsize) -> Option<()> {
+        // The lock is free when the next ticket is the one being served,
+        // in which case taking that ticket gets us straight in.
+        let word = state.load(Ordering::Relaxed);
+        if (word >> TICKET_SHIFT) & SERVING_MASK != word & SERVING_MASK {
+            return None;
+        }
+        state.compare_exchange(word, word.wrapping_add(1 << TICKET_SHIFT), Ordering::Acquire, Ordering::Relaxed).ok().map(|_| ())
+    }
+
+    fn unlock(state: &AtomicUsize, _token: ()) {
+        // A plain fetch_add would carry into the ticket counter when the
+        // serving counter wraps, so bump the lower half on its own.
+        let _ = state.fetch_update(Ordering::Release, Ordering::Relaxed, |word| {
+            Some((word & !SERVING_MASK) | (word.wrapping_add(1) & SERVING_MASK))
+        });
+    }
+}
+
+/// A TTAS lock that also disables interrupts while held.
+///
+/// Data shared with an interrupt handler needs this: if the handler fires
+/// while the lock is held on the same core, it would spin forever waiting
+/// for code it interrupted. The token remembers whether interrupts were
+/// on beforehand so nested IRQ-safe locks restore the right state.
+#[derive(Debug, Default)]
+pub struct IrqSafePolicy;
+
+impl LockPolicy for IrqSafePolicy {
+    type Token = bool;
+    const INIT: usize = UNLOCKED;
+
+    fn lock(state: &AtomicUsize) -> bool {
+        let were_enabled = interrupts::are_enabled();
+        interrupts::disable();
+        TtasPolicy::lock(state);
+        were_enabled
+    }
+
+    fn try_lock(state: &AtomicUsize) -> Option<bool> {
+        let were_enabled = interrupts::are_enabled();
+        interrupts::disable();
+        if TtasPolicy::try_lock(state).is_none() {
+            interrupts::restore(were_enabled);
+            return None;
+        }
+        Some(were_enabled)
+    }
+
+    fn unlock(state: &AtomicUsize, were_enabled: bool) {
+        TtasPolicy::unlock(state, ());
+        interrupts::restore(were_enabled);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Lock<T, P: LockPolicy> {
+    state: AtomicUsize,
     data: UnsafeCell<T>,
+    policy: PhantomData<P>,
 }
 
-unsafe impl<T: Send> Sync for SpinLock<T> {}
+pub type SpinLock<T> = Lock<T, TtasPolicy>;
+#[allow(dead_code)]
+pub type TicketLock<T> = Lock<T, TicketPolicy>;
+#[allow(dead_code)]
+pub type IrqSafeSpinLock<T> = Lock<T, IrqSafePolicy>;
+
+unsafe impl<T: Send, P: LockPolicy> Sync for Lock<T, P> {}
 
-pub struct SpinLockGuard<'a, T> {
-    lock: &'a SpinLock<T>,
+/// Like std's MutexGuard, a guard is never Send and is Sync only when T
+/// is. Left to the auto traits it would be both whenever T: Send, since
+/// they would follow the &Lock inside it. Sharing a &LockGuard shares a
+/// &T across cores, which needs T: Sync. Moving a guard would unlock on
+/// another core, which for IrqSafeSpinLock restores the wrong core's IF.
+pub struct LockGuard<'a, T, P: LockPolicy> {
+    lock: &'a Lock<T, P>,
+    token: P::Token,
+    not_send: PhantomData<*const ()>,
 }
 
-impl<T> SpinLock<T> {
+unsafe impl<T: Sync, P: LockPolicy> Sync for LockGuard<'_, T, P> {}
+
+// Build-time checks that the guard stays !Send and Sync. If it ever
+// became Send, both impls of AmbiguousIfSend would apply and the call
+// below couldn't pick one, so the build fails.
+const _: () = {
+    trait AmbiguousIfSend<A> {
+        fn some_item() {}
+    }
+    impl<T: ?Sized> AmbiguousIfSend<()> for T {}
+
+    struct Invalid;
+    impl<T: ?Sized + Send> AmbiguousIfSend<Invalid> for T {}
+
+    const _: fn() = || {
+        let _ = <LockGuard<'static, (), TtasPolicy> as AmbiguousIfSend<_>>::some_item;
+    };
+
+    fn assert_sync<T: Sync>() {}
+    const _: fn() = || assert_sync::<LockGuard<'static, u32, TtasPolicy>>();
+};
+
+#[allow(dead_code)]
+pub type SpinLockGuard<'a, T> = LockGuard<'a, T, TtasPolicy>;
+
+impl<T, P: LockPolicy> Lock<T, P> {
     pub fn new(item: T) -> Self {
         Self {
-            flag: AtomicBool::new(false),
+            state: AtomicUsize::new(P::INIT),
             // Use UnsafeCell for interior mutability.
             // This means that we can modify the data even with an immutable reference.
             // We need this for static references to ensure multiple parts
             // of our program can use this lock at runtime.
-            // In this case, thread safety is manually guaranteed by 
+            // In this case, thread safety is manually guaranteed by
             // the locking mechanisms, so we can do this.
             data: UnsafeCell::new(item),
-        } 
+            policy: PhantomData,
+        }
     }
 
-    pub fn acquire(&self) -> SpinLockGuard<T> {
-        while self.flag.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
-            while self.flag.load(Ordering::Relaxed) {
-                hint::spin_loop();
-            }
-        }
+    pub fn acquire(&self) -> LockGuard<'_, T, P> {
+        let token = P::lock(&self.state);
+        LockGuard { lock: self, token, not_send: PhantomData }
+    }
 
-        SpinLockGuard::<T> { lock: self }
+    /// Returns None instead of spinning if the lock is already held.
+    pub fn try_acquire(&self) -> Option<LockGuard<'_, T, P>> {
+        let token = P::try_lock(&self.state)?;
+        Some(LockGuard { lock: self, token, not_send: PhantomData })
     }
+}
 
+/// Policies with nothing to restore can still be released by hand.
+impl<T, P: LockPolicy<Token = ()>> Lock<T, P> {
+    #[allow(dead_code)]
     pub fn release(&self) {
-        self.flag.store(false, Ordering::Release);
+        P::unlock(&self.state, ());
     }
 }
 
 /// We want to use a smart pointer pattern similar to the Box type,
 /// so we implement the Deref and DerefMut to achieve this.
-impl<'a, T> Deref for SpinLockGuard<'a, T> {
+impl<'a, T, P: LockPolicy> Deref for LockGuard<'a, T, P> {
     type Target = T;
     fn deref(&self) -> &T {
-        unsafe { &*self.lock.data.get() } 
+        unsafe { &*self.lock.data.get() }
     }
 }
 
-impl<'a, T> DerefMut for SpinLockGuard<'a, T> {
+impl<'a, T, P: LockPolicy> DerefMut for LockGuard<'a, T, P> {
     fn deref_mut(&mut self) -> &mut T {
-       unsafe { &mut *self.lock.data.get() } 
+       unsafe { &mut *self.lock.data.get() }
     }
 }
 
-impl<'a, T> Drop for SpinLockGuard<'a, T> {
+impl<'a, T, P: LockPolicy> Drop for LockGuard<'a, T, P> {
     fn drop(&mut self) {
-        SpinLock::<T>::release(self.lock); 
+        P::unlock(&self.lock.state, self.token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::with_interrupts_enabled;
+
+    #[test_case]
+    fn ttas_try_acquire_fails_while_held() {
+        let lock = SpinLock::new(0);
+        let guard = lock.acquire();
+        assert!(lock.try_acquire().is_none());
+        drop(guard);
+        assert!(lock.try_acquire().is_some());
+    }
+
+    #[test_case]
+    fn ticket_counters_wrap_independently() {
+        // Both counters at their maximum: the lock is free, and taking it
+        // wraps the ticket counter while serving stays put.
+        let state = AtomicUsize::new(SERVING_MASK << TICKET_SHIFT | SERVING_MASK);
+        TicketPolicy::lock(&state);
+        assert_eq!(state.load(Ordering::Relaxed), SERVING_MASK);
+
+        // Serving wraps too, without carrying into the ticket counter.
+        TicketPolicy::unlock(&state, ());
+        assert_eq!(state.load(Ordering::Relaxed), 0);
+
+        assert!(TicketPolicy::try_lock(&state).is_some());
+        assert!(TicketPolicy::try_lock(&state).is_none());
+    }
+
+    #[test_case]
+    fn ticket_lock_acquires_and_releases() {
+        let lock = TicketLock::new(0);
+        for i in 0..3 {
+            *lock.acquire() += i;
+        }
+        assert_eq!(*lock.acquire(), 3);
+        let guard = lock.acquire();
+        assert!(lock.try_acquire().is_none());
+        drop(guard);
+        assert!(lock.try_acquire().is_some());
+    }
+
+    #[test_case]
+    fn irq_safe_try_lock_restores_if_on_failure() {
+        with_interrupts_enabled(|| {
+            let state = AtomicUsize::new(LOCKED);
+            assert!(IrqSafePolicy::try_lock(&state).is_none());
+            assert!(interrupts::are_enabled());
+        });
+    }
+
+    #[test_case]
+    fn irq_safe_guards_restore_if_when_the_outer_one_drops() {
+        with_interrupts_enabled(|| {
+            let outer = IrqSafeSpinLock::new(());
+            let inner = IrqSafeSpinLock::new(());
+
+            let outer_guard = outer.acquire();
+            assert!(!interrupts::are_enabled());
+            let inner_guard = inner.try_acquire().unwrap();
+            drop(inner_guard);
+            assert!(!interrupts::are_enabled());
+            drop(outer_guard);
+            assert!(interrupts::are_enabled());
+        });
     }
 }
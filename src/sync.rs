@@ -0,0 +1,21 @@
+//! Synchronization primitives for one-time initialization of kernel data
+//!
+//! The spinlock lives in its own module; the types here cover values
+//! that are written once and then only read, plus a borrow-checked cell
+//! for data that never leaves its core.
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+// Nothing per-CPU exists yet.
+#[allow(dead_code)]
+mod atomic_refcell;
+mod lazy;
+mod once;
+mod once_cell;
+
+#[allow(unused_imports)]
+pub use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+pub use lazy::Lazy;
+pub use once::Once;
+pub use once_cell::OnceCell;
@@ -0,0 +1,138 @@
+//! A RefCell that can live in a static, for data only one core touches
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    cell::UnsafeCell,
+    ops::{
+        Deref,
+        DerefMut,
+    },
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+/// The flag counts shared borrows, or is EXCLUSIVE while borrowed mutably.
+const UNUSED: usize = 0;
+const EXCLUSIVE: usize = usize::MAX;
+
+/// Per-CPU structures and early boot state are never shared between
+/// cores, so a SpinLock would only spin on our own mistakes. This keeps
+/// RefCell's borrow rules with a single atomic flag, and a conflicting
+/// borrow panics instead of waiting, which turns a borrow from an
+/// unexpected context (say, an interrupt handler) into a clear message
+/// rather than a deadlock or UB.
+///
+/// A borrow is one compare_exchange with no spinning. Acquire on success
+/// pairs with the Release on drop, so the cell stays sound even if it
+/// does end up crossing cores.
+pub struct AtomicRefCell<T> {
+    borrows: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send + Sync> Sync for AtomicRefCell<T> {}
+
+impl<T> AtomicRefCell<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            borrows: AtomicUsize::new(UNUSED),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// Panics if the cell is already borrowed mutably.
+    pub fn borrow(&self) -> AtomicRef<'_, T> {
+        let mut current = self.borrows.load(Ordering::Relaxed);
+        loop {
+            // One below EXCLUSIVE is the last count a shared borrow may
+            // take, so the counter can never overflow into it.
+            if current >= EXCLUSIVE - 1 {
+                panic!("AtomicRefCell already mutably borrowed");
+            }
+
+            match self.borrows.compare_exchange_weak(current, current + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return AtomicRef { cell: self },
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Panics if the cell is already borrowed at all.
+    pub fn borrow_mut(&self) -> AtomicRefMut<'_, T> {
+        if self.borrows.compare_exchange(UNUSED, EXCLUSIVE, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            panic!("AtomicRefCell already borrowed");
+        }
+        AtomicRefMut { cell: self }
+    }
+}
+
+impl<T: Default> Default for AtomicRefCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+pub struct AtomicRef<'a, T> {
+    cell: &'a AtomicRefCell<T>,
+}
+
+impl<T> Deref for AtomicRef<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.cell.data.get() }
+    }
+}
+
+impl<T> Drop for AtomicRef<'_, T> {
+    fn drop(&mut self) {
+        self.cell.borrows.fetch_sub(1, Ordering::Release);
+    }
+}
+
+pub struct AtomicRefMut<'a, T> {
+    cell: &'a AtomicRefCell<T>,
+}
+
+impl<T> Deref for AtomicRefMut<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.cell.data.get() }
+    }
+}
+
+impl<T> DerefMut for AtomicRefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.cell.data.get() }
+    }
+}
+
+impl<T> Drop for AtomicRefMut<'_, T> {
+    fn drop(&mut self) {
+        self.cell.borrows.store(UNUSED, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn shared_borrows_nest() {
+        let cell = AtomicRefCell::new(5u32);
+        {
+            let outer = cell.borrow();
+            let inner = cell.borrow();
+            assert_eq!(*outer + *inner, 10);
+            assert_eq!(cell.borrows.load(Ordering::Relaxed), 2);
+        }
+        assert_eq!(cell.borrows.load(Ordering::Relaxed), UNUSED);
+
+        // With both shared borrows gone a mutable one goes through.
+        *cell.borrow_mut() += 1;
+        assert_eq!(*cell.borrow(), 6);
+    }
+}
@@ -0,0 +1,91 @@
+//! A value that is initialized the first time it is dereferenced
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    cell::Cell,
+    ops::Deref,
+};
+
+use super::Once;
+
+/// Pairs a Once with the closure that fills it in.
+///
+/// This lets statics that need runtime setup be written directly:
+///
+/// static IDT: Lazy<InterruptDescriptorTable> = Lazy::new(|| { ... });
+///
+/// A non-capturing closure coerces to the default `fn() -> T`, so the
+/// type of the static does not need to name the closure.
+pub struct Lazy<T, F = fn() -> T> {
+    once: Once<T>,
+    init: Cell<Option<F>>,
+}
+
+/// The initializer is only ever taken by the core that wins the Once,
+/// so the Cell is never touched concurrently.
+unsafe impl<T: Send + Sync, F: Send> Sync for Lazy<T, F> {}
+
+impl<T, F> Lazy<T, F> {
+    pub const fn new(init: F) -> Self {
+        Self {
+            once: Once::new(),
+            init: Cell::new(Some(init)),
+        }
+    }
+
+    /// The value if it has already been initialized. Unlike force this
+    /// never runs or waits on the initializer, so it is safe where that
+    /// could hang, e.g. when the initializer is what panicked.
+    pub fn get(this: &Self) -> Option<&T> {
+        this.once.get()
+    }
+}
+
+impl<T, F: FnOnce() -> T> Lazy<T, F> {
+    /// Runs the initializer if nobody has yet and returns the value.
+    pub fn force(this: &Self) -> &T {
+        this.once.call_once(|| match this.init.take() {
+            Some(init) => init(),
+            None => panic!("Lazy initializer already taken"),
+        })
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for Lazy<T, F> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        Lazy::force(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    };
+
+    use super::*;
+
+    #[test_case]
+    fn initializer_runs_once() {
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+        let lazy: Lazy<u64> = Lazy::new(|| {
+            RUNS.fetch_add(1, Ordering::Relaxed);
+            0x6b_6f
+        });
+        assert!(Lazy::get(&lazy).is_none());
+        assert_eq!(RUNS.load(Ordering::Relaxed), 0);
+
+        let first: *const u64 = &*lazy;
+        for _ in 0..3 {
+            assert_eq!(*lazy, 0x6b_6f);
+            assert!(core::ptr::eq(&*lazy, first));
+        }
+        assert_eq!(Lazy::get(&lazy), Some(&0x6b_6f));
+        assert_eq!(RUNS.load(Ordering::Relaxed), 1);
+    }
+}
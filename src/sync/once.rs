@@ -0,0 +1,101 @@
+//! A cell that runs its initializer exactly once
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    cell::UnsafeCell,
+    hint,
+    mem::MaybeUninit,
+    sync::atomic::{
+        AtomicU8,
+        Ordering,
+    },
+};
+
+const INCOMPLETE: u8 = 0;
+const RUNNING: u8 = 1;
+const COMPLETE: u8 = 2;
+
+/// Holds a value that is produced by the first caller of `call_once`.
+///
+/// The state moves INCOMPLETE -> RUNNING -> COMPLETE. Only the core that
+/// wins the INCOMPLETE -> RUNNING exchange runs the initializer; every
+/// other caller spins until it observes COMPLETE. The Release store of
+/// COMPLETE pairs with the Acquire loads so the written value is visible
+/// before anyone hands out a reference to it.
+///
+/// Panics abort the kernel, so a panicking initializer never leaves the
+/// cell stuck in RUNNING for anyone else to observe.
+pub struct Once<T> {
+    state: AtomicU8,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// Sharing a Once shares the T inside it, and whichever core runs the
+/// initializer effectively sends the T to the others.
+unsafe impl<T: Send + Sync> Sync for Once<T> {}
+unsafe impl<T: Send> Send for Once<T> {}
+
+impl<T> Once<T> {
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(INCOMPLETE),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    pub fn call_once<F: FnOnce() -> T>(&self, f: F) -> &T {
+        if self.state.compare_exchange(INCOMPLETE, RUNNING, Ordering::Acquire, Ordering::Acquire).is_ok() {
+            // We won the race, so nobody else touches the data until COMPLETE is stored.
+            unsafe { (*self.data.get()).write(f()) };
+            self.state.store(COMPLETE, Ordering::Release);
+        } else {
+            while self.state.load(Ordering::Acquire) != COMPLETE {
+                hint::spin_loop();
+            }
+        }
+
+        unsafe { (*self.data.get()).assume_init_ref() }
+    }
+
+    /// The value if the initializer has finished, without waiting for
+    /// one that is still running.
+    pub fn get(&self) -> Option<&T> {
+        match self.state.load(Ordering::Acquire) {
+            COMPLETE => Some(unsafe { (*self.data.get()).assume_init_ref() }),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Default for Once<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Once<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == COMPLETE {
+            unsafe { self.data.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn get_before_and_after_call_once() {
+        let once = Once::new();
+        assert_eq!(once.get(), None);
+
+        assert_eq!(*once.call_once(|| 7u32), 7);
+        assert_eq!(once.get(), Some(&7));
+
+        // The value is already there, so a second initializer never runs.
+        assert_eq!(*once.call_once(|| 8), 7);
+    }
+}
@@ -0,0 +1,94 @@
+//! A write-once cell for values that become available partway through boot
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{
+        AtomicU8,
+        Ordering,
+    },
+};
+
+const EMPTY: u8 = 0;
+const WRITING: u8 = 1;
+const READY: u8 = 2;
+
+/// Unlike Once, there is no initializer: whoever has the value calls set.
+///
+/// The state moves EMPTY -> WRITING -> READY. Readers only hand out a
+/// reference after an Acquire load sees READY, which pairs with the
+/// Release store made once the value is written. A get that races with
+/// a set in progress sees WRITING and returns None rather than waiting.
+pub struct OnceCell<T> {
+    state: AtomicU8,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+unsafe impl<T: Send + Sync> Sync for OnceCell<T> {}
+unsafe impl<T: Send> Send for OnceCell<T> {}
+
+impl<T> OnceCell<T> {
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(EMPTY),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Store the value, or hand it back if the cell was already set.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self.state.compare_exchange(EMPTY, WRITING, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return Err(value);
+        }
+
+        unsafe { (*self.data.get()).write(value) };
+        self.state.store(READY, Ordering::Release);
+        Ok(())
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        match self.state.load(Ordering::Acquire) {
+            READY => Some(unsafe { (*self.data.get()).assume_init_ref() }),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for OnceCell<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == READY {
+            unsafe { self.data.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn second_set_hands_the_value_back() {
+        let cell = OnceCell::new();
+        assert_eq!(cell.set(1u32), Ok(()));
+        assert_eq!(cell.set(2), Err(2));
+        assert_eq!(cell.get(), Some(&1));
+    }
+
+    #[test_case]
+    fn get_is_none_while_writing() {
+        let cell = OnceCell::<u32>::new();
+        cell.state.store(WRITING, Ordering::Relaxed);
+        assert_eq!(cell.get(), None);
+
+        // Drop only reads data once READY, so leaving WRITING behind is fine.
+    }
+}
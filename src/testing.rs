@@ -0,0 +1,156 @@
+//! The in-kernel test runner, following https://os.phil-opp.com/testing/
+//!
+//! `cargo test` boots the kernel in QEMU with an isa-debug-exit device
+//! (see package.metadata.bootimage in Cargo.toml). Results go to serial,
+//! which QEMU forwards to stdio, and the exit code reports the outcome.
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::{
+    fmt,
+    panic::PanicInfo,
+};
+
+use crate::{
+    arch,
+    early_print,
+    early_println,
+    interrupts,
+    port::{
+        self,
+        Port,
+    },
+};
+
+/// QEMU exits with (code << 1) | 1, so Success shows up as 33, the
+/// test-success-exit-code in Cargo.toml.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum QemuExitCode {
+    Success = 0x10,
+    Failed = 0x11,
+}
+
+/// The port isa-debug-exit is configured at in Cargo.toml.
+const DEBUG_EXIT_PORT: u16 = 0xf4;
+
+pub fn exit_qemu(code: QemuExitCode) -> ! {
+    unsafe { Port::<u32>::new(DEBUG_EXIT_PORT) }.write(code as u32);
+    // Only reached without the exit device, e.g. a test kernel on real
+    // hardware.
+    arch::halt()
+}
+
+pub trait Testable {
+    fn run(&self);
+}
+
+impl<T: Fn()> Testable for T {
+    fn run(&self) {
+        early_print!("{}...\t", core::any::type_name::<T>());
+        self();
+        early_println!("[ok]");
+    }
+}
+
+pub fn test_runner(tests: &[&dyn Testable]) {
+    early_println!("Running {} tests", tests.len());
+    for test in tests {
+        test.run();
+    }
+    exit_qemu(QemuExitCode::Success);
+}
+
+pub fn panic(info: &PanicInfo) -> ! {
+    early_println!("[failed]\n");
+    early_println!("Error: {}\n", info);
+    exit_qemu(QemuExitCode::Failed);
+}
+
+/// A test that passes by panicking can't go through test_runner, since
+/// its first panic ends the kernel. Each one is an integration test of
+/// its own with harness = false, whose entry point calls this and whose
+/// panic handler calls panicked_as_expected.
+pub fn should_panic(name: &str, test: impl FnOnce()) -> ! {
+    early_print!("{}...\t", name);
+    test();
+    early_println!("[test did not panic]");
+    exit_qemu(QemuExitCode::Failed);
+}
+
+pub fn panicked_as_expected() -> ! {
+    early_println!("[ok]");
+    exit_qemu(QemuExitCode::Success);
+}
+
+/// Data ports of the two 8259 PICs. Writing 0xff masks every IRQ line.
+const PIC1_DATA: u16 = 0x21;
+const PIC2_DATA: u16 = 0xa1;
+
+/// Run f with IF set, for tests of the code that saves and restores it.
+///
+/// There is no IDT yet, so a real interrupt would triple fault. Masking
+/// every line on both PICs first keeps anything from arriving while IF
+/// is set. The masks and IF are restored afterwards.
+pub fn with_interrupts_enabled(f: impl FnOnce()) {
+    let were_enabled = interrupts::are_enabled();
+    let masks = unsafe { (port::inb(PIC1_DATA), port::inb(PIC2_DATA)) };
+    unsafe {
+        port::outb(PIC1_DATA, 0xff);
+        port::outb(PIC2_DATA, 0xff);
+    }
+
+    interrupts::enable();
+    f();
+    interrupts::disable();
+
+    unsafe {
+        port::outb(PIC1_DATA, masks.0);
+        port::outb(PIC2_DATA, masks.1);
+    }
+    interrupts::restore(were_enabled);
+}
+
+/// Collects formatted output in a fixed buffer so tests can compare
+/// Display output without a heap. Anything past N bytes is an error.
+pub struct FmtBuffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FmtBuffer<N> {
+    pub fn new() -> Self {
+        Self { bytes: [0; N], len: 0 }
+    }
+
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+}
+
+impl<const N: usize> Default for FmtBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for FmtBuffer<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > N {
+            return Err(fmt::Error);
+        }
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Format value with Display into a FmtBuffer.
+pub fn format<const N: usize>(value: impl fmt::Display) -> FmtBuffer<N> {
+    use core::fmt::Write;
+    let mut buffer = FmtBuffer::new();
+    write!(buffer, "{}", value).unwrap();
+    buffer
+}
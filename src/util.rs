@@ -0,0 +1,175 @@
+//! Small formatting helpers shared across the kernel
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+use core::fmt;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// Formats a byte slice as contiguous lowercase hex, e.g. "deadbeef",
+/// for use inline in println!.
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// The classic hexdump layout: address, sixteen hex bytes split into two
+/// groups of eight, then the printable ASCII in a gutter.
+///
+/// 00001000  48 65 6c 6c 6f 2c 20 47  75 6d 62 6f 21 0a 00 01  |Hello, Gumbo!...|
+///
+/// Addresses are printed with eight digits when they all fit, which keeps
+/// each line inside the 80 column VGA console, and sixteen otherwise.
+pub struct HexDump<'a> {
+    pub bytes: &'a [u8],
+    pub base_addr: u64,
+}
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let end = self.base_addr.saturating_add(self.bytes.len() as u64);
+        let width = if end <= u32::MAX as u64 + 1 { 8 } else { 16 };
+
+        for (i, line) in self.bytes.chunks(BYTES_PER_LINE).enumerate() {
+            let addr = self.base_addr.wrapping_add((i * BYTES_PER_LINE) as u64);
+            write!(f, "{:0width$x} ", addr, width = width)?;
+
+            for column in 0..BYTES_PER_LINE {
+                if column % 8 == 0 {
+                    write!(f, " ")?;
+                }
+                // Pad a short last line so the gutter lines up.
+                match line.get(column) {
+                    Some(byte) => write!(f, "{:02x} ", byte)?,
+                    None => write!(f, "   ")?,
+                }
+            }
+
+            write!(f, " |")?;
+            for &byte in line {
+                let c = match byte {
+                    0x20..=0x7e => byte as char,
+                    _ => '.',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f, "|")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a byte count with binary units, e.g. "128 MiB" or "1.50 GiB".
+///
+/// Values are rounded to two decimal places, which are left off when they
+/// would both be zero, so exact sizes read as "4 KiB" rather than
+/// "4.00 KiB". Use decimal() for 1000-based units, as disks are sold in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanBytes(pub u64);
+
+/// HumanBytes in 1000-based units, e.g. "1.50 GB".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalBytes(pub u64);
+
+const BINARY_UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+const DECIMAL_UNITS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+
+impl HumanBytes {
+    pub fn decimal(self) -> DecimalBytes {
+        DecimalBytes(self.0)
+    }
+}
+
+impl fmt::Display for HumanBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_size(f, self.0, 1024, &BINARY_UNITS)
+    }
+}
+
+impl fmt::Display for DecimalBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_size(f, self.0, 1000, &DECIMAL_UNITS)
+    }
+}
+
+/// Works in hundredths of a unit with integer math, since the kernel
+/// doesn't enable the FPU. Rounding can carry a value up to a whole
+/// base, e.g. 1023.999 KiB, so that case moves up to the next unit.
+fn format_size(f: &mut fmt::Formatter, bytes: u64, base: u64, units: &[&str]) -> fmt::Result {
+    if bytes < base {
+        return write!(f, "{} {}", bytes, units[0]);
+    }
+
+    let mut unit = 0;
+    let mut divisor: u128 = 1;
+    while unit + 1 < units.len() && bytes as u128 >= divisor * base as u128 {
+        unit += 1;
+        divisor *= base as u128;
+    }
+
+    let mut hundredths = (bytes as u128 * 100 + divisor / 2) / divisor;
+    if hundredths >= base as u128 * 100 && unit + 1 < units.len() {
+        unit += 1;
+        divisor *= base as u128;
+        hundredths = (bytes as u128 * 100 + divisor / 2) / divisor;
+    }
+
+    match hundredths % 100 {
+        0 => write!(f, "{} {}", hundredths / 100, units[unit]),
+        fraction => write!(f, "{}.{:02} {}", hundredths / 100, fraction, units[unit]),
+    }
+}
+
+/// Print bytes as a hexdump, labelling the first byte with base_addr.
+pub fn hexdump(bytes: &[u8], base_addr: u64) {
+    crate::print!("{}", HexDump { bytes, base_addr });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::format;
+
+    #[test_case]
+    fn hexdump_pads_short_last_line() {
+        let bytes = b"Hello, Gumbo!\n\x00\x01\x02\x03AB";
+        let dump = format::<256>(HexDump { bytes, base_addr: 0x1000 });
+        assert_eq!(
+            dump.as_str(),
+            "00001000  48 65 6c 6c 6f 2c 20 47  75 6d 62 6f 21 0a 00 01  |Hello, Gumbo!...|\n\
+             00001010  02 03 41 42                                       |..AB|\n",
+        );
+    }
+
+    #[test_case]
+    fn human_bytes_formats_binary_units() {
+        let cases: [(u64, &str); 8] = [
+            (0, "0 B"),
+            (1023, "1023 B"),
+            (1024, "1 KiB"),
+            (1536, "1.50 KiB"),
+            // Rounds to 1024.00 KiB, which carries up to the next unit.
+            (1_048_575, "1 MiB"),
+            (134_217_728, "128 MiB"),
+            (1_610_612_736, "1.50 GiB"),
+            (5 * 1024 * 1024 * 1024 + 123_456_789, "5.11 GiB"),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(format::<16>(HumanBytes(bytes)).as_str(), expected);
+        }
+    }
+
+    #[test_case]
+    fn human_bytes_decimal_uses_1000() {
+        assert_eq!(format::<16>(HumanBytes(999).decimal()).as_str(), "999 B");
+        assert_eq!(format::<16>(HumanBytes(1000).decimal()).as_str(), "1 kB");
+        assert_eq!(format::<16>(HumanBytes(1_610_612_736).decimal()).as_str(), "1.61 GB");
+    }
+}
@@ -11,22 +11,37 @@
 ///    15     |    Blink
 ///
 /// The enum below contain the binary representations for the colors.
-use lazy_static::lazy_static;
-use volatile::Volatile;
-use core::fmt;
+use core::{
+    fmt,
+    ops::Range,
+};
 
-use crate::spinlock::SpinLock;
+use crate::{
+    mmio::{
+        Mmio,
+        Volatile,
+    },
+    port,
+    spinlock::SpinLock,
+    sync::Lazy,
+};
 
-/// The spinlock must be initialized at runtime, so we use the following
-/// macro to safely perform this initialization.
-lazy_static! {
-    // Use ref to ensure that match statements create a reference instead of trying to move the value 
-    pub static ref WRITER: SpinLock<Writer> = SpinLock::new(Writer {
-        column_position: 0,
-        color_code: ColorCode::new(Color::Yellow, Color::Black),
-        buffer: unsafe { &mut *(0xb8000 as *mut Buffer) },
-    }); 
-}
+/// The text buffer is mapped at 0xb8000. A const so new's alignment
+/// check happens at compile time.
+const VGA_BUFFER: Mmio<Buffer> = unsafe { Mmio::new(0xb8000) };
+
+// The spinlock must be initialized at runtime, so we use Lazy
+// to safely perform this initialization on first use.
+pub static WRITER: Lazy<SpinLock<Writer>> = Lazy::new(|| SpinLock::new(Writer {
+    width: DEFAULT_WIDTH,
+    height: DEFAULT_HEIGHT,
+    row_position: DEFAULT_HEIGHT - 1,
+    column_position: 0,
+    color_code: ColorCode::new(Color::Yellow, Color::Black),
+    buffer: unsafe { VGA_BUFFER.as_mut() },
+    saved_cursors: [(0, 0); CURSOR_STACK_DEPTH],
+    saved_count: 0,
+}));
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -67,21 +82,72 @@ struct ScreenChar {
     color_code: ColorCode,
 }
 
-const BUFFER_HEIGHT: usize = 25;
-const BUFFER_WIDTH: usize = 80;
+/// The standard text mode the BIOS leaves us in.
+const DEFAULT_WIDTH: usize = 80;
+const DEFAULT_HEIGHT: usize = 25;
+
+/// Cells laid out in the buffer, enough for 80x50, the largest grid we
+/// program. Text memory runs to 0xbffff, so there is room for more.
+const MAX_CELLS: usize = 80 * 50;
+
+/// How many save_cursor calls can be nested before restoring.
+const CURSOR_STACK_DEPTH: usize = 4;
 
 #[repr(transparent)]
 struct Buffer {
     /// Use Volatile to ensure compiler does not optimize away reads or rights
     /// since it is not aware of side effects of writing to this buffer.
-    /// Must use volatile_read and volatile_write now.
-    chars: [[Volatile<ScreenChar>; BUFFER_WIDTH]; BUFFER_HEIGHT],
+    /// Must use Volatile's read and write now.
+    ///
+    /// The hardware stores rows back to back, width cells each, so the
+    /// layout depends on the mode and the buffer is one flat array.
+    chars: [Volatile<ScreenChar>; MAX_CELLS],
+}
+
+/// Every access to the buffer goes through these helpers so the bounds
+/// check lives in one place, cell_range. In debug builds a bad position
+/// panics with the offending cells. Release builds skip that check, but
+/// the index into the flat array is still checked, so a bad position can
+/// land on the wrong cell and never outside the buffer.
+impl Writer {
+    fn write_cell(&mut self, row: usize, col: usize, character: ScreenChar) {
+        let index = self.cell_range(row, col, 1).start;
+        self.buffer.chars[index].write(character);
+    }
+
+    fn read_cell(&self, row: usize, col: usize) -> ScreenChar {
+        let index = self.cell_range(row, col, 1).start;
+        self.buffer.chars[index].read()
+    }
+
+    /// The len cells from (row, col) onwards, for writing a run in one
+    /// go. The run has to stay on that row.
+    fn row_cells_mut(&mut self, row: usize, col: usize, len: usize) -> &mut [Volatile<ScreenChar>] {
+        let range = self.cell_range(row, col, len);
+        &mut self.buffer.chars[range]
+    }
+
+    fn cell_range(&self, row: usize, col: usize, len: usize) -> Range<usize> {
+        debug_assert!(
+            row < self.height && col + len <= self.width,
+            "VGA cells out of bounds: row {} cols {}..{} (buffer is {}x{})",
+            row, col, col + len, self.width, self.height,
+        );
+        let start = row * self.width + col;
+        start..start + len
+    }
 }
 
 pub struct Writer {
+    width: usize,
+    height: usize,
+    row_position: usize,
     column_position: usize,
     color_code: ColorCode,
     buffer: &'static mut Buffer,
+    /// Stack of (row, col) pairs pushed by save_cursor, like ANSI ESC[s / ESC[u.
+    saved_cursors: [(usize, usize); CURSOR_STACK_DEPTH],
+    saved_count: usize,
 }
 
 impl Writer { 
@@ -89,15 +155,15 @@ impl Writer {
         match byte {
             b'\n' => self.new_line(),
             byte => {
-                if self.column_position >= BUFFER_WIDTH {
+                if self.column_position >= self.width {
                     self.new_line();
                 }
 
-                let row = BUFFER_HEIGHT - 1;
+                let row = self.row_position;
                 let col = self.column_position;
                 let color_code = self.color_code;
                 
-                self.buffer.chars[row][col].write(ScreenChar {
+                self.write_cell(row, col, ScreenChar {
                     ascii_character: byte,
                     color_code,
                 });
@@ -107,25 +173,130 @@ impl Writer {
     }
 
     fn new_line(&mut self) {
-        for row in 1..BUFFER_HEIGHT {
-            for col in 0..BUFFER_WIDTH {
-                let character = self.buffer.chars[row][col].read();
-                self.buffer.chars[row - 1][col].write(character);
+        // A restored cursor may sit above the bottom row, so move down
+        // to the bottom before we start scrolling.
+        if self.row_position < self.height - 1 {
+            self.row_position += 1;
+            self.column_position = 0;
+            return;
+        }
+
+        for row in 1..self.height {
+            for col in 0..self.width {
+                let character = self.read_cell(row, col);
+                self.write_cell(row - 1, col, character);
             }
         }
-        self.clear_row(BUFFER_HEIGHT - 1);
+        self.clear_row(self.height - 1);
         self.column_position = 0;
     }
 
     fn clear_row(&mut self, row: usize) {
-        for col in 0..BUFFER_WIDTH {
-            self.buffer.chars[row][col].write(ScreenChar {
+        for col in 0..self.width {
+            self.write_cell(row, col, ScreenChar {
                 ascii_character: b' ',
                 color_code: self.color_code,
             })
         }
     }
 
+    /// Switch to a width x height grid, e.g. after changing the text mode.
+    /// The screen is cleared since the old contents would be laid out for
+    /// the wrong width, and the cursor moves to the bottom row as at boot.
+    pub fn set_dimensions(&mut self, width: usize, height: usize) {
+        assert!(
+            width > 0 && height > 0 && width * height <= MAX_CELLS,
+            "{}x{} does not fit the VGA buffer", width, height,
+        );
+        self.width = width;
+        self.height = height;
+
+        for row in 0..height {
+            self.clear_row(row);
+        }
+        self.row_position = height - 1;
+        self.column_position = 0;
+
+        // Saved cursors might be off the new grid, so clamp them.
+        for (row, col) in &mut self.saved_cursors[..self.saved_count] {
+            *row = (*row).min(height - 1);
+            *col = (*col).min(width);
+        }
+    }
+
+    /// Draw byte at (row, col) in the current color without moving the
+    /// cursor, e.g. for a status line that stays put while text scrolls.
+    /// Debug builds panic if (row, col) is off the grid.
+    pub fn put_char(&mut self, row: usize, col: usize, byte: u8) {
+        let color_code = self.color_code;
+        self.write_cell(row, col, ScreenChar {
+            ascii_character: byte,
+            color_code,
+        });
+    }
+
+    pub fn set_color(&mut self, foreground: Color, background: Color) {
+        self.color_code = ColorCode::new(foreground, background);
+    }
+
+    /// Remember the current position so restore_cursor can return to it.
+    /// Saves beyond CURSOR_STACK_DEPTH are dropped.
+    #[allow(dead_code)]
+    pub fn save_cursor(&mut self) {
+        if self.saved_count < CURSOR_STACK_DEPTH {
+            self.saved_cursors[self.saved_count] = (self.row_position, self.column_position);
+            self.saved_count += 1;
+        }
+    }
+
+    /// Jump back to the most recently saved position. Does nothing if
+    /// there is no saved position.
+    #[allow(dead_code)]
+    pub fn restore_cursor(&mut self) {
+        if self.saved_count > 0 {
+            self.saved_count -= 1;
+            let (row, col) = self.saved_cursors[self.saved_count];
+            self.row_position = row;
+            self.column_position = col;
+        }
+    }
+
+    /// Write a large pre-formatted buffer a line at a time instead of a
+    /// byte at a time. Each pass copies the run of bytes up to the next
+    /// newline or the end of the row straight into consecutive cells,
+    /// then wraps. Like write_string, anything outside printable ASCII
+    /// shows as 0xfe.
+    #[allow(dead_code)]
+    pub fn write_bytes_raw(&mut self, bytes: &[u8]) {
+        let mut rest = bytes;
+        while let Some(&first) = rest.first() {
+            if first == b'\n' {
+                self.new_line();
+                rest = &rest[1..];
+                continue;
+            }
+            if self.column_position >= self.width {
+                self.new_line();
+            }
+
+            let room = (self.width - self.column_position).min(rest.len());
+            let run = rest[..room].iter().position(|&byte| byte == b'\n').unwrap_or(room);
+
+            let color_code = self.color_code;
+            let cells = self.row_cells_mut(self.row_position, self.column_position, run);
+            for (cell, &byte) in cells.iter_mut().zip(&rest[..run]) {
+                let ascii_character = match byte {
+                    0x20..=0x7e => byte,
+                    _ => 0xfe,
+                };
+                cell.write(ScreenChar { ascii_character, color_code });
+            }
+
+            self.column_position += run;
+            rest = &rest[run..];
+        }
+    }
+
     pub fn write_string(&mut self, s: &str) {
         for byte in s.bytes() {
             match byte {
@@ -145,6 +316,116 @@ impl fmt::Write for Writer {
     }
 }
 
+/// CRTC registers, selected by writing an index to the address port.
+const CRTC_ADDRESS: u16 = 0x3d4;
+const CRTC_MAX_SCAN_LINE: u8 = 0x09;
+const CRTC_CURSOR_START: u8 = 0x0a;
+const CRTC_CURSOR_END: u8 = 0x0b;
+
+/// The low five bits of the maximum scan line register are the character
+/// height minus one.
+const SCAN_LINE_MASK: u8 = 0x1f;
+
+/// Sequencer and graphics controller registers, indexed the same way.
+const SEQUENCER_ADDRESS: u16 = 0x3c4;
+const SEQUENCER_MAP_MASK: u8 = 0x02;
+const SEQUENCER_MEMORY_MODE: u8 = 0x04;
+const GRAPHICS_ADDRESS: u16 = 0x3ce;
+const GRAPHICS_READ_MAP: u8 = 0x04;
+const GRAPHICS_MODE: u8 = 0x05;
+const GRAPHICS_MISC: u8 = 0x06;
+
+/// The font lives in plane 2, one glyph every 32 bytes with a byte per
+/// scan line. load_8x8_font maps it at 0xa0000 while it works on it,
+/// which the bootloader identity maps along with the text buffer.
+const FONT_PLANE: Mmio<Font> = unsafe { Mmio::new(0xa0000) };
+const GLYPH_STRIDE: usize = 32;
+
+#[repr(transparent)]
+struct Font {
+    glyphs: [[Volatile<u8>; GLYPH_STRIDE]; 256],
+}
+
+/// Read the register at index behind the address port. Its data port is
+/// always the next one up.
+unsafe fn read_register(address: u16, index: u8) -> u8 {
+    unsafe {
+        port::outb(address, index);
+        port::inb(address + 1)
+    }
+}
+
+unsafe fn write_register(address: u16, index: u8, value: u8) {
+    unsafe {
+        port::outb(address, index);
+        port::outb(address + 1, value);
+    }
+}
+
+/// Switch to 80x50 text by drawing characters 8 scan lines tall instead
+/// of 16. The 400 line display then fits 50 rows.
+///
+/// The glyphs come from the font in plane 2, and the BIOS's is 8x16, so
+/// it's squashed to 8x8 first or every character would lose its bottom
+/// half.
+#[allow(dead_code)]
+pub fn set_80x50() {
+    // Holding the writer keeps anyone from printing while text memory is
+    // mapped out for the font.
+    let mut writer = WRITER.acquire();
+
+    unsafe {
+        load_8x8_font();
+
+        let max_scan_line = read_register(CRTC_ADDRESS, CRTC_MAX_SCAN_LINE);
+        write_register(CRTC_ADDRESS, CRTC_MAX_SCAN_LINE, (max_scan_line & !SCAN_LINE_MASK) | 7);
+
+        // Keep the underline cursor on the last two lines of the cell.
+        write_register(CRTC_ADDRESS, CRTC_CURSOR_START, 6);
+        write_register(CRTC_ADDRESS, CRTC_CURSOR_END, 7);
+    }
+
+    writer.set_dimensions(80, 50);
+}
+
+/// Squash the 8x16 font in plane 2 into 8x8 in place. Line r of each new
+/// glyph ORs lines 2r and 2r + 1 of the old one, so one-pixel strokes
+/// like the dot on an i survive. Going from the top down, line r is only
+/// overwritten once nothing below it needs it.
+///
+/// Safety: text memory is unmapped until this returns, so nothing may
+/// touch the text buffer meanwhile.
+unsafe fn load_8x8_font() {
+    unsafe {
+        let map_mask = read_register(SEQUENCER_ADDRESS, SEQUENCER_MAP_MASK);
+        let memory_mode = read_register(SEQUENCER_ADDRESS, SEQUENCER_MEMORY_MODE);
+        let read_map = read_register(GRAPHICS_ADDRESS, GRAPHICS_READ_MAP);
+        let mode = read_register(GRAPHICS_ADDRESS, GRAPHICS_MODE);
+        let misc = read_register(GRAPHICS_ADDRESS, GRAPHICS_MISC);
+
+        // Read and write plane 2 alone, addressed sequentially rather than
+        // interleaved odd/even, and mapped at 0xa0000.
+        write_register(SEQUENCER_ADDRESS, SEQUENCER_MAP_MASK, 0x04);
+        write_register(SEQUENCER_ADDRESS, SEQUENCER_MEMORY_MODE, 0x07);
+        write_register(GRAPHICS_ADDRESS, GRAPHICS_READ_MAP, 0x02);
+        write_register(GRAPHICS_ADDRESS, GRAPHICS_MODE, 0x00);
+        write_register(GRAPHICS_ADDRESS, GRAPHICS_MISC, 0x04);
+
+        for glyph in &mut FONT_PLANE.as_mut().glyphs {
+            for line in 0..8 {
+                let squashed = glyph[2 * line].read() | glyph[2 * line + 1].read();
+                glyph[line].write(squashed);
+            }
+        }
+
+        write_register(SEQUENCER_ADDRESS, SEQUENCER_MAP_MASK, map_mask);
+        write_register(SEQUENCER_ADDRESS, SEQUENCER_MEMORY_MODE, memory_mode);
+        write_register(GRAPHICS_ADDRESS, GRAPHICS_READ_MAP, read_map);
+        write_register(GRAPHICS_ADDRESS, GRAPHICS_MODE, mode);
+        write_register(GRAPHICS_ADDRESS, GRAPHICS_MISC, misc);
+    }
+}
+
 /// Create our macros for printing to the VGA buffer
 #[macro_export]
 macro_rules! print {
@@ -157,8 +438,170 @@ macro_rules! println {
     ($($arg:tt)*) => ($crate::print!("{}\n", format_args!($($arg)*)));
 }
 
+/// Printing that can never block or panic, for the panic handler and
+/// early boot. The message is dropped if someone else holds the writer
+/// or if the writer hasn't been set up yet, since setting it up could
+/// mean waiting on another core. Only a plain string is accepted so no
+/// core::fmt machinery runs.
+/// Both macros evaluate to whether the message was printed.
+#[macro_export]
+macro_rules! try_print {
+    ($s:expr) => ($crate::vga_buffer::_try_print($s, false));
+}
+
+#[macro_export]
+macro_rules! try_println {
+    () => ($crate::vga_buffer::_try_print("", true));
+    ($s:expr) => ($crate::vga_buffer::_try_print($s, true));
+}
+
+#[doc(hidden)]
+pub fn _try_print(s: &str, newline: bool) -> bool {
+    match Lazy::get(&WRITER).and_then(|writer| writer.try_acquire()) {
+        Some(mut writer) => {
+            writer.write_string(s);
+            if newline {
+                writer.write_byte(b'\n');
+            }
+            true
+        }
+        None => false,
+    }
+}
+
 #[doc(hidden)]
 pub fn _print(args: fmt::Arguments) {
     use core::fmt::Write;
     WRITER.acquire().write_fmt(args).unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that row holds text followed by blanks, all in the current color.
+    fn assert_row(writer: &Writer, row: usize, text: &[u8]) {
+        for col in 0..writer.width {
+            let cell = writer.read_cell(row, col);
+            let expected = text.get(col).copied().unwrap_or(b' ');
+            assert_eq!(cell.ascii_character, expected, "row {} col {}", row, col);
+            assert_eq!(cell.color_code, writer.color_code);
+        }
+    }
+
+    #[test_case]
+    fn write_bytes_raw_blits_lines_and_wraps() {
+        let mut writer = WRITER.acquire();
+        writer.set_dimensions(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+
+        // A line with a non-printable byte, then one that wraps 3 past the
+        // end of the row.
+        let mut input = [b'w'; 8 + DEFAULT_WIDTH + 3];
+        input[..8].copy_from_slice(b"\nhello\x01\n");
+        writer.write_bytes_raw(&input);
+
+        let bottom = DEFAULT_HEIGHT - 1;
+        assert_row(&writer, bottom - 2, b"hello\xfe");
+        assert_row(&writer, bottom - 1, &[b'w'; DEFAULT_WIDTH]);
+        assert_row(&writer, bottom, b"www");
+        assert_eq!(writer.column_position, 3);
+    }
+
+    /// A cleared default-size screen with nothing saved, cursor at the
+    /// start of the bottom row.
+    fn reset(writer: &mut Writer) {
+        writer.set_dimensions(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+        writer.saved_count = 0;
+    }
+
+    #[test_case]
+    fn restore_cursor_returns_to_the_saved_position() {
+        let mut writer = WRITER.acquire();
+        reset(&mut writer);
+
+        writer.write_string("12345");
+        writer.save_cursor();
+        writer.write_string("abc");
+        writer.restore_cursor();
+        writer.write_string("XY");
+
+        assert_row(&writer, DEFAULT_HEIGHT - 1, b"12345XYc");
+        assert_eq!(writer.column_position, 7);
+    }
+
+    #[test_case]
+    fn saved_cursors_nest() {
+        let mut writer = WRITER.acquire();
+        reset(&mut writer);
+
+        writer.save_cursor();
+        writer.write_string("ab");
+        writer.save_cursor();
+        writer.write_string("cd");
+        writer.restore_cursor();
+        writer.write_string("Z");
+        writer.restore_cursor();
+        writer.write_string("Y");
+
+        assert_row(&writer, DEFAULT_HEIGHT - 1, b"YbZd");
+    }
+
+    #[test_case]
+    fn saves_past_the_stack_depth_are_dropped() {
+        let mut writer = WRITER.acquire();
+        reset(&mut writer);
+
+        // Save at columns 0 through CURSOR_STACK_DEPTH, one more than fits.
+        for _ in 0..=CURSOR_STACK_DEPTH {
+            writer.save_cursor();
+            writer.write_byte(b'-');
+        }
+        assert_eq!(writer.saved_count, CURSOR_STACK_DEPTH);
+
+        for col in (0..CURSOR_STACK_DEPTH).rev() {
+            writer.restore_cursor();
+            assert_eq!((writer.row_position, writer.column_position), (DEFAULT_HEIGHT - 1, col));
+        }
+
+        // Nothing left, so this one stays put.
+        writer.write_string("ab");
+        writer.restore_cursor();
+        assert_eq!(writer.column_position, 2);
+    }
+
+    #[test_case]
+    fn taller_grid_scrolls_at_its_own_bottom_row() {
+        let mut writer = WRITER.acquire();
+        writer.set_dimensions(80, 50);
+        assert_eq!(writer.row_position, 49);
+
+        // More lines than the 25-row grid has, each one a letter.
+        for line in 0..30u8 {
+            writer.write_byte(b'a' + line);
+            writer.write_byte(b'\n');
+        }
+
+        // Every newline scrolled, so the last letter sits just above the
+        // blank bottom row and the first one 30 rows up from there.
+        assert_eq!(writer.row_position, 49);
+        assert_row(&writer, 49, b"");
+        assert_row(&writer, 48, &[b'a' + 29]);
+        assert_row(&writer, 19, b"a");
+        assert_row(&writer, 18, b"");
+
+        writer.set_dimensions(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+    }
+
+    #[test_case]
+    fn try_println_gives_up_while_writer_is_held() {
+        let _held = WRITER.acquire();
+        assert!(!try_println!("dropped"));
+        assert!(!try_println!());
+    }
+
+    #[test_case]
+    fn try_println_prints_when_writer_is_free() {
+        Lazy::force(&WRITER);
+        assert!(try_println!("printed"));
+    }
+}
@@ -0,0 +1,33 @@
+//! borrow_mut while a shared borrow is alive must panic rather than hand
+//! out a &mut that aliases the shared one.
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+use gumbo_os::{
+    arch::entry::BootInfoCommon,
+    sync::AtomicRefCell,
+    testing,
+};
+
+gumbo_os::kernel_entry!(main);
+
+fn main(boot_info: BootInfoCommon) -> ! {
+    gumbo_os::init(&boot_info);
+
+    testing::should_panic("atomic_refcell_conflict::borrow_mut_while_borrowed", || {
+        let cell = AtomicRefCell::new(0u32);
+        let _shared = cell.borrow();
+        let _exclusive = cell.borrow_mut();
+    })
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    testing::panicked_as_expected()
+}
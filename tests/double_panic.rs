@@ -0,0 +1,39 @@
+//! A panic from inside the panic handler must still get its "double
+//! panic" line out on serial, instead of recursing until the stack runs
+//! out. early_serial writes synchronously, so by the time report returns
+//! for the second panic the line has gone out, just before [ok].
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+use gumbo_os::{
+    arch::entry::BootInfoCommon,
+    panicking,
+    testing::{
+        self,
+        QemuExitCode,
+    },
+};
+
+gumbo_os::kernel_entry!(main);
+
+fn main(boot_info: BootInfoCommon) -> ! {
+    gumbo_os::init(&boot_info);
+
+    testing::should_panic("double_panic::panic_in_panic_handler", || panic!("first panic"))
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    match panicking::report(info) {
+        // Panic again from inside the handler, like a handler bug would.
+        0 => panic!("second panic"),
+        1 => testing::panicked_as_expected(),
+        _ => testing::exit_qemu(QemuExitCode::Failed),
+    }
+}
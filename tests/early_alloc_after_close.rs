@@ -0,0 +1,36 @@
+//! early_alloc must panic once close says the heap has taken over.
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+#![no_std]
+#![no_main]
+
+use core::{
+    alloc::Layout,
+    panic::PanicInfo,
+};
+
+use gumbo_os::{
+    arch::entry::BootInfoCommon,
+    early_alloc,
+    testing,
+};
+
+gumbo_os::kernel_entry!(main);
+
+fn main(boot_info: BootInfoCommon) -> ! {
+    gumbo_os::init(&boot_info);
+
+    testing::should_panic("early_alloc_after_close::alloc_panics", || {
+        let layout = Layout::new::<u64>();
+        assert!(early_alloc::alloc(layout).is_some());
+        early_alloc::close();
+        let _ = early_alloc::alloc(layout);
+    })
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    testing::panicked_as_expected()
+}
@@ -0,0 +1,34 @@
+//! A write below the last row must panic instead of landing in a cell
+//! that belongs to no row on screen. Only debug builds check, and cargo
+//! test builds debug.
+//!
+//! By: Arthur Riechert
+//! Date: Wed. Oct 14, 2026
+
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+use gumbo_os::{
+    arch::entry::BootInfoCommon,
+    testing,
+    vga_buffer::WRITER,
+};
+
+gumbo_os::kernel_entry!(main);
+
+fn main(boot_info: BootInfoCommon) -> ! {
+    gumbo_os::init(&boot_info);
+
+    testing::should_panic("vga_out_of_bounds::put_char_below_last_row", || {
+        let mut writer = WRITER.acquire();
+        writer.set_dimensions(80, 25);
+        writer.put_char(25, 0, b'x');
+    })
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    testing::panicked_as_expected()
+}